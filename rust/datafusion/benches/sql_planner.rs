@@ -0,0 +1,71 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+#[macro_use]
+extern crate criterion;
+use criterion::Criterion;
+
+use std::sync::Arc;
+
+extern crate arrow;
+extern crate datafusion;
+
+use arrow::datatypes::{DataType, Field, Schema};
+use datafusion::error::Result;
+
+use datafusion::datasource::MemTable;
+use datafusion::execution::context::ExecutionContext;
+
+fn plan(ctx: &ExecutionContext, sql: &str) {
+    ctx.create_logical_plan(sql).unwrap();
+}
+
+fn create_context(column_count: usize) -> Result<ExecutionContext> {
+    // define a wide schema.
+    let schema = Arc::new(Schema::new(
+        (0..column_count)
+            .map(|i| Field::new(&format!("c{}", i), DataType::Int32, false))
+            .collect(),
+    ));
+
+    let mut ctx = ExecutionContext::new();
+
+    // the planner only needs the schema of the table
+    let provider = MemTable::new(schema, vec![vec![]])?;
+    ctx.register_table("t", Box::new(provider));
+
+    Ok(ctx)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let column_count = 200;
+    let ctx = create_context(column_count).unwrap();
+
+    let projection = (0..column_count)
+        .map(|i| format!("c{}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let predicate = (0..column_count)
+        .map(|i| format!("c{} > {}", i, i))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let sql = format!("SELECT {} FROM t WHERE {}", projection, predicate);
+    c.bench_function("plan_200_columns", |b| b.iter(|| plan(&ctx, &sql)));
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
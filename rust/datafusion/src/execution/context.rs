@@ -133,14 +133,31 @@ impl ExecutionContext {
                 ref location,
                 ref file_type,
                 ref has_header,
+                ref delimiter,
+                ref compression,
+                ref partition_columns,
+                if_not_exists,
             } => match file_type {
+                _ if if_not_exists && self.state.datasources.contains_key(name) => {
+                    let plan = LogicalPlanBuilder::empty().build()?;
+                    Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+                }
+                _ if !partition_columns.is_empty() => {
+                    Err(DataFusionError::NotImplemented(
+                        "Partitioned external tables are not supported yet".to_string(),
+                    ))
+                }
+                _ if compression.is_some() => Err(DataFusionError::NotImplemented(
+                    "Compressed external tables are not supported yet".to_string(),
+                )),
                 FileType::CSV => {
                     self.register_csv(
                         name,
                         location,
                         CsvReadOptions::new()
                             .schema(&schema)
-                            .has_header(*has_header),
+                            .has_header(*has_header)
+                            .delimiter(*delimiter),
                     )?;
                     let plan = LogicalPlanBuilder::empty().build()?;
                     Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
@@ -156,6 +173,12 @@ impl ExecutionContext {
                 ))),
             },
 
+            LogicalPlan::DropTable { ref name, .. } => {
+                self.state.datasources.remove(name);
+                let plan = LogicalPlanBuilder::empty().build()?;
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+            }
+
             plan => Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan))),
         }
     }
@@ -163,6 +186,16 @@ impl ExecutionContext {
     /// Creates a logical plan. This function is intended for internal use and should not be
     /// called directly.
     pub fn create_logical_plan(&self, sql: &str) -> Result<LogicalPlan> {
+        self.create_logical_plan_with_warnings(sql)
+            .map(|(plan, _)| plan)
+    }
+
+    /// Creates a logical plan, along with the warnings of the SQL planner, such
+    /// as the unknown settings of a `SETTINGS` clause that were ignored.
+    pub fn create_logical_plan_with_warnings(
+        &self,
+        sql: &str,
+    ) -> Result<(LogicalPlan, Vec<String>)> {
         let statements = DFParser::parse_sql(sql)?;
 
         if statements.len() != 1 {
@@ -173,7 +206,8 @@ impl ExecutionContext {
 
         // create a query planner
         let query_planner = SqlToRel::new(&self.state);
-        Ok(query_planner.statement_to_plan(&statements[0])?)
+        let plan = query_planner.statement_to_plan(&statements[0])?;
+        Ok((plan, query_planner.warnings()))
     }
 
     /// Register variable
@@ -512,6 +546,20 @@ impl SchemaProvider for ExecutionContextState {
             .get(name)
             .and_then(|func| Some(func.clone()))
     }
+    fn table_names(&self) -> Vec<String> {
+        self.datasources.keys().cloned().collect()
+    }
+
+    fn get_variable_meta(&self, names: &[String]) -> Option<DataType> {
+        let var_type = if names.first()?.starts_with("@@") {
+            VarType::System
+        } else {
+            VarType::UserDefined
+        };
+        self.var_provider
+            .get(&var_type)
+            .and_then(|provider| provider.get_type(names))
+    }
 }
 
 impl FunctionRegistry for ExecutionContextState {
@@ -582,6 +630,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn create_logical_plan_with_settings_warnings() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("dual", test::create_table_dual());
+
+        let sql = "SELECT id FROM dual LIMIT 1 SETTINGS max_rows = 5, cache = 1";
+        let (plan, warnings) = ctx.create_logical_plan_with_warnings(sql)?;
+        let expected = "Settings: max_rows=5\
+                        \n  Limit: 1\
+                        \n    Projection: #id\
+                        \n      TableScan: dual projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+        assert_eq!(
+            vec!["Ignoring unknown setting 'cache'".to_string()],
+            warnings
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn create_variable_expr() -> Result<()> {
         let tmp_dir = TempDir::new()?;
@@ -620,6 +687,66 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn variable_meta() {
+        let mut ctx = ExecutionContext::new();
+        let names = |name: &str| vec![name.to_string()];
+        assert_eq!(ctx.state.get_variable_meta(&names("@@version")), None);
+
+        ctx.register_variable(
+            VarType::System,
+            Arc::new(test::variable::SystemVar::new()),
+        );
+        ctx.register_variable(
+            VarType::UserDefined,
+            Arc::new(test::variable::UserDefinedVar::new()),
+        );
+        assert_eq!(
+            ctx.state.get_variable_meta(&names("@@version")),
+            Some(DataType::Utf8)
+        );
+        assert_eq!(
+            ctx.state.get_variable_meta(&names("@name")),
+            Some(DataType::Utf8)
+        );
+        assert_eq!(ctx.state.get_variable_meta(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn describe_table() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let mut ctx = create_ctx(&tmp_dir, 1)?;
+
+        let results = collect(&mut ctx, "DESCRIBE test").await?;
+
+        let batch = &results[0];
+        assert_eq!(
+            field_names(batch),
+            vec!["column_name", "data_type", "is_nullable"]
+        );
+        let rows: Vec<Vec<String>> = (0..batch.num_rows())
+            .map(|row| {
+                batch
+                    .columns()
+                    .iter()
+                    .map(|column| {
+                        let column = column
+                            .as_any()
+                            .downcast_ref::<StringArray>()
+                            .expect("failed to cast column");
+                        column.value(row).to_string()
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![vec!["c1", "UInt32", "NO"], vec!["c2", "UInt64", "NO"],]
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn parallel_query_with_filter() -> Result<()> {
         let tmp_dir = TempDir::new()?;
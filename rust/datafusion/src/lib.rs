@@ -133,6 +133,7 @@
 //! * Sort: [`SortExec`](physical_plan::sort::SortExec)
 //! * Merge (partitions): [`MergeExec`](physical_plan::merge::MergeExec)
 //! * Limit: [`LocalLimitExec`](physical_plan::limit::LocalLimitExec) and [`GlobalLimitExec`](physical_plan::limit::GlobalLimitExec)
+//! * Offset: [`OffsetExec`](physical_plan::limit::OffsetExec)
 //! * Scan a CSV: [`CsvExec`](physical_plan::csv::CsvExec)
 //! * Scan a Parquet: [`ParquetExec`](physical_plan::parquet::ParquetExec)
 //! * Scan from memory: [`MemoryExec`](physical_plan::memory::MemoryExec)
@@ -22,7 +22,10 @@
 //! physical query plans and executed.
 
 use fmt::Debug;
-use std::{any::Any, collections::HashMap, collections::HashSet, fmt, sync::Arc};
+use std::{
+    any::Any, collections::BTreeMap, collections::HashMap, collections::HashSet, fmt,
+    sync::Arc,
+};
 
 use aggregates::{AccumulatorFunctionImplementation, StateTypeFunction};
 use arrow::{
@@ -41,11 +44,13 @@ use crate::{
 use crate::{
     physical_plan::{
         aggregates, expressions::binary_operator_data_type, functions, udf::ScalarUDF,
+        window_functions,
     },
     sql::parser::FileType,
 };
 use arrow::record_batch::RecordBatch;
 use functions::{ReturnTypeFunction, ScalarFunctionImplementation, Signature};
+use sqlparser::ast::WindowFrame;
 
 mod operators;
 pub use operators::Operator;
@@ -73,7 +78,8 @@ fn create_name(e: &Expr, input_schema: &Schema) -> Result<String> {
     match e {
         Expr::Alias(_, name) => Ok(name.clone()),
         Expr::Column(name) => Ok(name.clone()),
-        Expr::ScalarVariable(variable_names) => Ok(variable_names.join(".")),
+        Expr::ScalarVariable(_, variable_names) => Ok(variable_names.join(".")),
+        Expr::OuterColumn(name, _) => Ok(format!("outer_ref({})", name)),
         Expr::Literal(value) => Ok(format!("{:?}", value)),
         Expr::BinaryExpr { left, op, right } => {
             let left = create_name(left, input_schema)?;
@@ -84,6 +90,10 @@ fn create_name(e: &Expr, input_schema: &Schema) -> Result<String> {
             let expr = create_name(expr, input_schema)?;
             Ok(format!("CAST({} AS {:?})", expr, data_type))
         }
+        Expr::TryCast { expr, data_type } => {
+            let expr = create_name(expr, input_schema)?;
+            Ok(format!("TRY_CAST({} AS {:?})", expr, data_type))
+        }
         Expr::Not(expr) => {
             let expr = create_name(expr, input_schema)?;
             Ok(format!("NOT {}", expr))
@@ -106,8 +116,66 @@ fn create_name(e: &Expr, input_schema: &Schema) -> Result<String> {
             fun,
             distinct,
             args,
-            ..
-        } => create_function_name(&fun.to_string(), *distinct, args, input_schema),
+            filter,
+        } => {
+            let name = match (fun, args.as_slice()) {
+                // the planner rewrites `COUNT(*)` to `COUNT(1)`; name it after the former
+                (
+                    aggregates::AggregateFunction::Count,
+                    [Expr::Literal(ScalarValue::UInt8(Some(1)))],
+                ) => {
+                    let distinct_str = if *distinct { "DISTINCT " } else { "" };
+                    format!("{}({}*)", fun, distinct_str)
+                }
+                _ => {
+                    create_function_name(&fun.to_string(), *distinct, args, input_schema)?
+                }
+            };
+            match filter {
+                Some(filter) => Ok(format!(
+                    "{} FILTER (WHERE {})",
+                    name,
+                    create_name(filter, input_schema)?
+                )),
+                None => Ok(name),
+            }
+        }
+        Expr::WindowFunction {
+            fun,
+            args,
+            partition_by,
+            order_by,
+            window_frame,
+        } => {
+            let name = create_function_name(&fun.to_string(), false, args, input_schema)?;
+            let partition_by = partition_by
+                .iter()
+                .map(|e| create_name(e, input_schema))
+                .collect::<Result<Vec<_>>>()?;
+            let order_by = order_by
+                .iter()
+                .map(|e| create_name(e, input_schema))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(format!(
+                "{} {}",
+                name,
+                fmt_window_spec(&partition_by, &order_by, window_frame)
+            ))
+        }
+        Expr::Sort {
+            expr,
+            asc,
+            nulls_first,
+        } => {
+            let expr = create_name(expr, input_schema)?;
+            let asc = if *asc { "ASC" } else { "DESC" };
+            let nulls = if *nulls_first {
+                "NULLS FIRST"
+            } else {
+                "NULLS LAST"
+            };
+            Ok(format!("{} {} {}", expr, asc, nulls))
+        }
         Expr::AggregateUDF { fun, args } => {
             let mut names = Vec::with_capacity(args.len());
             for e in args {
@@ -115,6 +183,11 @@ fn create_name(e: &Expr, input_schema: &Schema) -> Result<String> {
             }
             Ok(format!("{}({})", fun.name, names.join(",")))
         }
+        Expr::Exists { negated, .. } => Ok(fmt_exists(*negated)),
+        Expr::InSubquery { expr, negated, .. } => {
+            let expr = create_name(expr, input_schema)?;
+            Ok(fmt_in_subquery(&expr, *negated))
+        }
         other => Err(DataFusionError::NotImplemented(format!(
             "Physical plan does not support logical expression {:?}",
             other
@@ -127,6 +200,26 @@ pub fn exprlist_to_fields(expr: &[Expr], input_schema: &Schema) -> Result<Vec<Fi
     expr.iter().map(|e| e.to_field(input_schema)).collect()
 }
 
+/// Expands the grouping sets in `group_expr` into the distinct expressions they
+/// group by, in order of first appearance. Other expressions are kept as they are.
+pub fn grouping_set_to_exprlist(group_expr: &[Expr]) -> Vec<Expr> {
+    let mut exprs: Vec<Expr> = vec![];
+    for e in group_expr {
+        match e {
+            Expr::GroupingSet(sets) => {
+                for e in sets.iter().flatten() {
+                    let name = format!("{:?}", e);
+                    if !exprs.iter().any(|x| format!("{:?}", x) == name) {
+                        exprs.push(e.clone());
+                    }
+                }
+            }
+            _ => exprs.push(e.clone()),
+        }
+    }
+    exprs
+}
+
 /// `Expr` is a logical expression. A logical expression is something like `1 + 1`, or `CAST(c1 AS int)`.
 /// Logical expressions know how to compute its [arrow::datatypes::DataType] and nullability.
 /// `Expr` is a central struct of DataFusion's query API.
@@ -148,8 +241,8 @@ pub enum Expr {
     Alias(Box<Expr>, String),
     /// A named reference to a field in a schema.
     Column(String),
-    /// A named reference to a variable in a registry.
-    ScalarVariable(Vec<String>),
+    /// A named reference to a variable in a registry, along with the type of its value.
+    ScalarVariable(DataType, Vec<String>),
     /// A constant value.
     Literal(ScalarValue),
     /// A binary expression such as "age > 21"
@@ -176,6 +269,14 @@ pub enum Expr {
         /// The `DataType` the expression will yield
         data_type: DataType,
     },
+    /// Casts the expression to a given type like `Cast`, except that the values
+    /// that cannot be converted yield NULL instead of an error.
+    TryCast {
+        /// The expression being cast
+        expr: Box<Expr>,
+        /// The `DataType` the expression will yield
+        data_type: DataType,
+    },
     /// A sort expression, that can be used to sort values.
     Sort {
         /// The expression to sort on
@@ -207,6 +308,22 @@ pub enum Expr {
         args: Vec<Expr>,
         /// Whether this is a DISTINCT aggregation or not
         distinct: bool,
+        /// Optional predicate restricting the rows that are aggregated,
+        /// e.g. `FILTER (WHERE a > 1)`
+        filter: Option<Box<Expr>>,
+    },
+    /// Represents the call of a window function, e.g. `ROW_NUMBER() OVER (...)`
+    WindowFunction {
+        /// Name of the function
+        fun: window_functions::WindowFunction,
+        /// List of expressions to feed to the functions as arguments
+        args: Vec<Expr>,
+        /// The expressions the rows are partitioned by
+        partition_by: Vec<Expr>,
+        /// The sort expressions ordering the rows of a partition
+        order_by: Vec<Expr>,
+        /// Optional frame of the window, relative to the current row
+        window_frame: Option<WindowFrame>,
     },
     /// aggregate function
     AggregateUDF {
@@ -217,6 +334,30 @@ pub enum Expr {
     },
     /// Represents a reference to all fields in a schema.
     Wildcard,
+    /// Whether a subquery returns at least one row, e.g. `EXISTS (SELECT ...)`
+    Exists {
+        /// The logical plan of the subquery
+        subquery: Arc<LogicalPlan>,
+        /// Whether the expression is negated (`NOT EXISTS`)
+        negated: bool,
+    },
+    /// Whether the value of an expression is one of the rows of a single column
+    /// subquery, e.g. `id IN (SELECT ...)`
+    InSubquery {
+        /// The expression to look for
+        expr: Box<Expr>,
+        /// The logical plan of the subquery
+        subquery: Arc<LogicalPlan>,
+        /// Whether the expression is negated (`NOT IN`)
+        negated: bool,
+    },
+    /// A column of an enclosing query, with its type, referenced by a
+    /// correlated subquery
+    OuterColumn(String, DataType),
+    /// The grouping sets of an aggregation, e.g. those implied by
+    /// `GROUP BY ROLLUP (a, b)`. Only valid as the grouping expression of an
+    /// `Aggregate`, whose output has one column per distinct expression of the sets.
+    GroupingSet(Vec<Vec<Expr>>),
 }
 
 impl Expr {
@@ -231,9 +372,11 @@ impl Expr {
         match self {
             Expr::Alias(expr, _) => expr.get_type(schema),
             Expr::Column(name) => Ok(schema.field_with_name(name)?.data_type().clone()),
-            Expr::ScalarVariable(_) => Ok(DataType::Utf8),
+            Expr::ScalarVariable(data_type, _) => Ok(data_type.clone()),
+            Expr::OuterColumn(_, data_type) => Ok(data_type.clone()),
             Expr::Literal(l) => Ok(l.get_datatype()),
             Expr::Cast { data_type, .. } => Ok(data_type.clone()),
+            Expr::TryCast { data_type, .. } => Ok(data_type.clone()),
             Expr::ScalarUDF { fun, args } => {
                 let data_types = args
                     .iter()
@@ -262,6 +405,13 @@ impl Expr {
                     .collect::<Result<Vec<_>>>()?;
                 Ok((fun.return_type)(&data_types)?.as_ref().clone())
             }
+            Expr::WindowFunction { fun, args, .. } => {
+                let data_types = args
+                    .iter()
+                    .map(|e| e.get_type(schema))
+                    .collect::<Result<Vec<_>>>()?;
+                window_functions::return_type(fun, &data_types)
+            }
             Expr::Not(_) => Ok(DataType::Boolean),
             Expr::IsNull(_) => Ok(DataType::Boolean),
             Expr::IsNotNull(_) => Ok(DataType::Boolean),
@@ -279,6 +429,11 @@ impl Expr {
                 "Wildcard expressions are not valid in a logical query plan".to_owned(),
             )),
             Expr::Nested(e) => e.get_type(schema),
+            Expr::Exists { .. } => Ok(DataType::Boolean),
+            Expr::InSubquery { .. } => Ok(DataType::Boolean),
+            Expr::GroupingSet(_) => Err(DataFusionError::Internal(
+                "Grouping sets are only valid as grouping expressions".to_owned(),
+            )),
         }
     }
 
@@ -293,12 +448,15 @@ impl Expr {
             Expr::Alias(expr, _) => expr.nullable(input_schema),
             Expr::Column(name) => Ok(input_schema.field_with_name(name)?.is_nullable()),
             Expr::Literal(value) => Ok(value.is_null()),
-            Expr::ScalarVariable(_) => Ok(true),
+            Expr::ScalarVariable(_, _) => Ok(true),
+            Expr::OuterColumn(_, _) => Ok(true),
             Expr::Cast { expr, .. } => expr.nullable(input_schema),
+            Expr::TryCast { .. } => Ok(true),
             Expr::ScalarFunction { .. } => Ok(true),
             Expr::ScalarUDF { .. } => Ok(true),
             Expr::AggregateFunction { .. } => Ok(true),
             Expr::AggregateUDF { .. } => Ok(true),
+            Expr::WindowFunction { .. } => Ok(true),
             Expr::Not(expr) => expr.nullable(input_schema),
             Expr::IsNull(_) => Ok(false),
             Expr::IsNotNull(_) => Ok(false),
@@ -309,9 +467,15 @@ impl Expr {
             } => Ok(left.nullable(input_schema)? || right.nullable(input_schema)?),
             Expr::Sort { ref expr, .. } => expr.nullable(input_schema),
             Expr::Nested(e) => e.nullable(input_schema),
+            Expr::Exists { .. } => Ok(false),
+            // a null value, or a null row of the subquery, makes the result unknown
+            Expr::InSubquery { .. } => Ok(true),
             Expr::Wildcard => Err(DataFusionError::Internal(
                 "Wildcard expressions are not valid in a logical query plan".to_owned(),
             )),
+            Expr::GroupingSet(_) => Err(DataFusionError::Internal(
+                "Grouping sets are only valid as grouping expressions".to_owned(),
+            )),
         }
     }
 
@@ -414,6 +578,36 @@ impl Expr {
         binary_expr(self.clone(), Operator::NotLike, other.clone())
     }
 
+    /// Null-safe inequality: two nulls are not distinct from each other
+    pub fn is_distinct_from(&self, other: Expr) -> Expr {
+        binary_expr(self.clone(), Operator::IsDistinctFrom, other)
+    }
+
+    /// Null-safe equality: two nulls are not distinct from each other
+    pub fn is_not_distinct_from(&self, other: Expr) -> Expr {
+        binary_expr(self.clone(), Operator::IsNotDistinctFrom, other)
+    }
+
+    /// `IS TRUE`: true only when the expression is true, never null
+    pub fn is_true(&self) -> Expr {
+        self.is_not_distinct_from(lit(true))
+    }
+
+    /// `IS NOT TRUE`: true when the expression is false or null
+    pub fn is_not_true(&self) -> Expr {
+        self.is_distinct_from(lit(true))
+    }
+
+    /// `IS FALSE`: true only when the expression is false, never null
+    pub fn is_false(&self) -> Expr {
+        self.is_not_distinct_from(lit(false))
+    }
+
+    /// `IS UNKNOWN`: equivalent to `IS NULL` for boolean expressions
+    pub fn is_unknown(&self) -> Expr {
+        Expr::IsNull(Box::new(self.clone()))
+    }
+
     /// Alias
     pub fn alias(&self, name: &str) -> Expr {
         Expr::Alias(Box::new(self.clone()), name.to_owned())
@@ -462,6 +656,7 @@ pub fn min(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Min,
         distinct: false,
         args: vec![expr],
+        filter: None,
     }
 }
 
@@ -471,6 +666,7 @@ pub fn max(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Max,
         distinct: false,
         args: vec![expr],
+        filter: None,
     }
 }
 
@@ -480,6 +676,7 @@ pub fn sum(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Sum,
         distinct: false,
         args: vec![expr],
+        filter: None,
     }
 }
 
@@ -489,6 +686,7 @@ pub fn avg(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Avg,
         distinct: false,
         args: vec![expr],
+        filter: None,
     }
 }
 
@@ -498,6 +696,7 @@ pub fn count(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Count,
         distinct: false,
         args: vec![expr],
+        filter: None,
     }
 }
 
@@ -603,6 +802,12 @@ pub fn array(args: Vec<Expr>) -> Expr {
     }
 }
 
+/// returns the grouping sets `sets` of an aggregation, for use as a grouping
+/// expression. An empty set groups all rows together into a grand total.
+pub fn grouping_sets(sets: Vec<Vec<Expr>>) -> Expr {
+    Expr::GroupingSet(sets)
+}
+
 /// Creates a new UDF with a specific signature and specific return type.
 /// This is a helper function to create a new UDF.
 /// The function `create_udf` returns a subset of all possible `ScalarFunction`:
@@ -652,16 +857,58 @@ fn fmt_function(
     write!(f, "{}({}{})", fun, distinct_str, args.join(", "))
 }
 
+fn fmt_window_spec(
+    partition_by: &[String],
+    order_by: &[String],
+    window_frame: &Option<WindowFrame>,
+) -> String {
+    let mut clauses = vec![];
+    if !partition_by.is_empty() {
+        clauses.push(format!("PARTITION BY {}", partition_by.join(", ")));
+    }
+    if !order_by.is_empty() {
+        clauses.push(format!("ORDER BY {}", order_by.join(", ")));
+    }
+    if let Some(frame) = window_frame {
+        clauses.push(match &frame.end_bound {
+            Some(end_bound) => format!(
+                "{} BETWEEN {} AND {}",
+                frame.units, frame.start_bound, end_bound
+            ),
+            None => format!("{} {}", frame.units, frame.start_bound),
+        });
+    }
+    format!("OVER ({})", clauses.join(" "))
+}
+
+fn fmt_exists(negated: bool) -> String {
+    match negated {
+        true => "NOT EXISTS (<subquery>)".to_string(),
+        false => "EXISTS (<subquery>)".to_string(),
+    }
+}
+
+fn fmt_in_subquery(expr: &str, negated: bool) -> String {
+    match negated {
+        true => format!("{} NOT IN (<subquery>)", expr),
+        false => format!("{} IN (<subquery>)", expr),
+    }
+}
+
 impl fmt::Debug for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Expr::Alias(expr, alias) => write!(f, "{:?} AS {}", expr, alias),
             Expr::Column(name) => write!(f, "#{}", name),
-            Expr::ScalarVariable(var_names) => write!(f, "{}", var_names.join(".")),
+            Expr::ScalarVariable(_, var_names) => write!(f, "{}", var_names.join(".")),
+            Expr::OuterColumn(name, _) => write!(f, "outer_ref(#{})", name),
             Expr::Literal(v) => write!(f, "{:?}", v),
             Expr::Cast { expr, data_type } => {
                 write!(f, "CAST({:?} AS {:?})", expr, data_type)
             }
+            Expr::TryCast { expr, data_type } => {
+                write!(f, "TRY_CAST({:?} AS {:?})", expr, data_type)
+            }
             Expr::Not(expr) => write!(f, "NOT {:?}", expr),
             Expr::IsNull(expr) => write!(f, "{:?} IS NULL", expr),
             Expr::IsNotNull(expr) => write!(f, "{:?} IS NOT NULL", expr),
@@ -694,13 +941,52 @@ impl fmt::Debug for Expr {
                 fun,
                 distinct,
                 ref args,
-                ..
-            } => fmt_function(f, &fun.to_string(), *distinct, args),
+                filter,
+            } => {
+                fmt_function(f, &fun.to_string(), *distinct, args)?;
+                match filter {
+                    Some(filter) => write!(f, " FILTER (WHERE {:?})", filter),
+                    None => Ok(()),
+                }
+            }
             Expr::AggregateUDF { fun, ref args, .. } => {
                 fmt_function(f, &fun.name, false, args)
             }
+            Expr::WindowFunction {
+                fun,
+                args,
+                partition_by,
+                order_by,
+                window_frame,
+            } => {
+                fmt_function(f, &fun.to_string(), false, args)?;
+                let partition_by: Vec<String> =
+                    partition_by.iter().map(|e| format!("{:?}", e)).collect();
+                let order_by: Vec<String> =
+                    order_by.iter().map(|e| format!("{:?}", e)).collect();
+                write!(
+                    f,
+                    " {}",
+                    fmt_window_spec(&partition_by, &order_by, window_frame)
+                )
+            }
             Expr::Wildcard => write!(f, "*"),
             Expr::Nested(expr) => write!(f, "({:?})", expr),
+            Expr::Exists { negated, .. } => write!(f, "{}", fmt_exists(*negated)),
+            Expr::InSubquery { expr, negated, .. } => {
+                write!(f, "{}", fmt_in_subquery(&format!("{:?}", expr), *negated))
+            }
+            Expr::GroupingSet(sets) => {
+                let sets: Vec<String> = sets
+                    .iter()
+                    .map(|set| {
+                        let exprs: Vec<String> =
+                            set.iter().map(|e| format!("{:?}", e)).collect();
+                        format!("({})", exprs.join(", "))
+                    })
+                    .collect();
+                write!(f, "GROUPING SETS ({})", sets.join(", "))
+            }
         }
     }
 }
@@ -772,6 +1058,19 @@ pub enum TableSource {
     FromProvider(Arc<dyn TableProvider + Send + Sync>),
 }
 
+/// The type of a join
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinType {
+    /// Only the rows that match in both inputs
+    Inner,
+    /// All the rows of the left input, and the matching rows of the right input
+    Left,
+    /// All the rows of the right input, and the matching rows of the left input
+    Right,
+    /// All the rows of both inputs
+    Full,
+}
+
 /// A LogicalPlan represents the different types of relational
 /// operators (such as Projection, Filter, etc) and can be created by
 /// the SQL query planner and the DataFrame API.
@@ -818,6 +1117,32 @@ pub enum LogicalPlan {
         /// The schema description of the aggregate output
         schema: SchemaRef,
     },
+    /// Evaluates window functions over its input, extending each row with
+    /// their results.
+    Window {
+        /// The incoming logical plan
+        input: Arc<LogicalPlan>,
+        /// Window function expressions
+        window_expr: Vec<Expr>,
+        /// The schema description of the window output
+        schema: SchemaRef,
+    },
+    /// Joins two inputs on the equality of pairs of their columns. The columns of
+    /// a pair that share their name appear once in the output, with the value of
+    /// the left input, of the right input for a right join, and of whichever input
+    /// has the row for a full join.
+    Join {
+        /// The left input
+        left: Arc<LogicalPlan>,
+        /// The right input
+        right: Arc<LogicalPlan>,
+        /// The pairs of (left, right) columns that must be equal
+        on: Vec<(String, String)>,
+        /// The type of the join
+        join_type: JoinType,
+        /// The schema description of the join output
+        schema: SchemaRef,
+    },
     /// Sorts its input according to a list of sort expressions.
     Sort {
         /// The sort expressions
@@ -887,6 +1212,54 @@ pub enum LogicalPlan {
         /// The logical plan
         input: Arc<LogicalPlan>,
     },
+    /// Discards the first `n` tuples from its input and produces the rest.
+    Offset {
+        /// The number of tuples to discard
+        n: usize,
+        /// The logical plan
+        input: Arc<LogicalPlan>,
+    },
+    /// Query-level settings, such as `max_rows`, that apply to the execution
+    /// of its input. They are only captured for now: the physical planner does
+    /// not apply them.
+    Settings {
+        /// The settings, by name
+        settings: BTreeMap<String, String>,
+        /// The logical plan
+        input: Arc<LogicalPlan>,
+    },
+    /// Drops a table.
+    DropTable {
+        /// The (empty) output schema
+        schema: SchemaRef,
+        /// The table name
+        name: String,
+        /// Whether dropping an unknown table is a no-op rather than an error
+        if_exists: bool,
+    },
+    /// Updates the rows of a table produced by its input, assigning the value of
+    /// an expression to each of the named columns.
+    Update {
+        /// The table name
+        table_name: String,
+        /// The updated columns and the expressions of their new values, over the
+        /// schema of the input
+        assignments: Vec<(String, Expr)>,
+        /// The rows to update: a scan of the table, filtered by the WHERE clause
+        input: Arc<LogicalPlan>,
+        /// The (empty) output schema
+        schema: SchemaRef,
+    },
+    /// Produces a relation with one row per column of a table, giving the column's
+    /// name, data type and nullability
+    DescribeTable {
+        /// The table name
+        name: String,
+        /// The schema of the described table
+        table_schema: SchemaRef,
+        /// The output schema of the describe (3 columns of text)
+        schema: SchemaRef,
+    },
     /// Creates an external table.
     CreateExternalTable {
         /// The table schema
@@ -899,6 +1272,14 @@ pub enum LogicalPlan {
         file_type: FileType,
         /// Whether the CSV file contains a header
         has_header: bool,
+        /// The field delimiter of the CSV file
+        delimiter: u8,
+        /// The compression codec of the files, if they are compressed
+        compression: Option<String>,
+        /// The columns the table is partitioned by
+        partition_columns: Vec<String>,
+        /// Whether the table is left untouched if it already exists
+        if_not_exists: bool,
     },
     /// Produces a relation with string representations of
     /// various parts of the plan
@@ -912,6 +1293,16 @@ pub enum LogicalPlan {
         /// The output schema of the explain (2 columns of text)
         schema: SchemaRef,
     },
+    /// Executes its input and produces a relation describing the runtime
+    /// metrics collected during the execution
+    Analyze {
+        /// Should extra (detailed, intermediate plans) be included?
+        verbose: bool,
+        /// The logical plan that is being EXPLAIN ANALYZE'd
+        input: Arc<LogicalPlan>,
+        /// The output schema of the analyze (2 columns of text)
+        schema: SchemaRef,
+    },
     /// Extension operator defined outside of DataFusion
     Extension {
         /// The runtime extension operator
@@ -939,10 +1330,18 @@ impl LogicalPlan {
             LogicalPlan::Projection { schema, .. } => &schema,
             LogicalPlan::Filter { input, .. } => input.schema(),
             LogicalPlan::Aggregate { schema, .. } => &schema,
+            LogicalPlan::Window { schema, .. } => &schema,
+            LogicalPlan::Join { schema, .. } => &schema,
             LogicalPlan::Sort { input, .. } => input.schema(),
             LogicalPlan::Limit { input, .. } => input.schema(),
+            LogicalPlan::Offset { input, .. } => input.schema(),
+            LogicalPlan::Settings { input, .. } => input.schema(),
             LogicalPlan::CreateExternalTable { schema, .. } => &schema,
+            LogicalPlan::DropTable { schema, .. } => &schema,
+            LogicalPlan::Update { schema, .. } => &schema,
+            LogicalPlan::DescribeTable { schema, .. } => &schema,
             LogicalPlan::Explain { schema, .. } => &schema,
+            LogicalPlan::Analyze { schema, .. } => &schema,
             LogicalPlan::Extension { node } => &node.schema(),
         }
     }
@@ -954,6 +1353,15 @@ impl LogicalPlan {
             Field::new("plan", DataType::Utf8, false),
         ]))
     }
+
+    /// Returns the (fixed) output schema for describe plans
+    pub fn describe_schema() -> SchemaRef {
+        SchemaRef::new(Schema::new(vec![
+            Field::new("column_name", DataType::Utf8, false),
+            Field::new("data_type", DataType::Utf8, false),
+            Field::new("is_nullable", DataType::Utf8, false),
+        ]))
+    }
 }
 
 impl LogicalPlan {
@@ -1026,6 +1434,27 @@ impl LogicalPlan {
                 )?;
                 input.fmt_with_indent(f, indent + 1)
             }
+            LogicalPlan::Window {
+                ref input,
+                ref window_expr,
+                ..
+            } => {
+                write!(f, "Window: windowExpr=[{:?}]", window_expr)?;
+                input.fmt_with_indent(f, indent + 1)
+            }
+            LogicalPlan::Join {
+                ref left,
+                ref right,
+                ref on,
+                join_type,
+                ..
+            } => {
+                let on: Vec<String> =
+                    on.iter().map(|(l, r)| format!("#{} = #{}", l, r)).collect();
+                write!(f, "Join: type={:?}, on=[{}]", join_type, on.join(", "))?;
+                left.fmt_with_indent(f, indent + 1)?;
+                right.fmt_with_indent(f, indent + 1)
+            }
             LogicalPlan::Sort {
                 ref input,
                 ref expr,
@@ -1046,13 +1475,72 @@ impl LogicalPlan {
                 write!(f, "Limit: {}", n)?;
                 input.fmt_with_indent(f, indent + 1)
             }
+            LogicalPlan::Offset {
+                ref input, ref n, ..
+            } => {
+                write!(f, "Offset: {}", n)?;
+                input.fmt_with_indent(f, indent + 1)
+            }
+            LogicalPlan::Settings {
+                ref settings,
+                ref input,
+            } => {
+                let settings: Vec<String> = settings
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect();
+                write!(f, "Settings: {}", settings.join(", "))?;
+                input.fmt_with_indent(f, indent + 1)
+            }
             LogicalPlan::CreateExternalTable { ref name, .. } => {
                 write!(f, "CreateExternalTable: {:?}", name)
             }
+            LogicalPlan::DropTable {
+                ref name,
+                if_exists,
+                ..
+            } => {
+                if if_exists {
+                    write!(f, "DropTable: {:?} if_exists=true", name)
+                } else {
+                    write!(f, "DropTable: {:?}", name)
+                }
+            }
+            LogicalPlan::Update {
+                ref table_name,
+                ref assignments,
+                ref input,
+                ..
+            } => {
+                let assignments: Vec<String> = assignments
+                    .iter()
+                    .map(|(column, value)| format!("{} = {:?}", column, value))
+                    .collect();
+                write!(
+                    f,
+                    "Update: {:?} set=[{}]",
+                    table_name,
+                    assignments.join(", ")
+                )?;
+                input.fmt_with_indent(f, indent + 1)
+            }
+            LogicalPlan::DescribeTable { ref name, .. } => {
+                write!(f, "DescribeTable: {:?}", name)
+            }
             LogicalPlan::Explain { ref plan, .. } => {
                 write!(f, "Explain")?;
                 plan.fmt_with_indent(f, indent + 1)
             }
+            LogicalPlan::Analyze {
+                verbose, ref input, ..
+            } => {
+                if verbose {
+                    write!(f, "Analyze: verbose=true")?;
+                } else {
+                    write!(f, "Analyze")?;
+                }
+                input.fmt_with_indent(f, indent + 1)
+            }
             LogicalPlan::Extension { ref node } => {
                 node.fmt_for_explain(f)?;
                 node.inputs()
@@ -1222,6 +1710,14 @@ impl LogicalPlanBuilder {
         }))
     }
 
+    /// Apply an offset
+    pub fn offset(&self, n: usize) -> Result<Self> {
+        Ok(Self::from(&LogicalPlan::Offset {
+            n,
+            input: Arc::new(self.plan.clone()),
+        }))
+    }
+
     /// Apply a sort
     pub fn sort(&self, expr: Vec<Expr>) -> Result<Self> {
         Ok(Self::from(&LogicalPlan::Sort {
@@ -1232,12 +1728,22 @@ impl LogicalPlanBuilder {
 
     /// Apply an aggregate
     pub fn aggregate(&self, group_expr: Vec<Expr>, aggr_expr: Vec<Expr>) -> Result<Self> {
-        let mut all_expr: Vec<Expr> = group_expr.clone();
+        let grouping_expr = grouping_set_to_exprlist(&group_expr);
+        let mut all_expr: Vec<Expr> = grouping_expr.clone();
         aggr_expr.iter().for_each(|x| all_expr.push(x.clone()));
 
         validate_unique_names("Aggregations", &all_expr, self.plan.schema())?;
 
-        let aggr_schema = Schema::new(exprlist_to_fields(&all_expr, self.plan.schema())?);
+        let mut fields = exprlist_to_fields(&grouping_expr, self.plan.schema())?;
+        if group_expr.iter().any(|e| matches!(e, Expr::GroupingSet(_))) {
+            // a grouping column is NULL in the rows of the sets that do not contain it
+            fields = fields
+                .iter()
+                .map(|f| Field::new(f.name(), f.data_type().clone(), true))
+                .collect();
+        }
+        fields.extend(exprlist_to_fields(&aggr_expr, self.plan.schema())?);
+        let aggr_schema = Schema::new(fields);
 
         Ok(Self::from(&LogicalPlan::Aggregate {
             input: Arc::new(self.plan.clone()),
@@ -1247,6 +1753,97 @@ impl LogicalPlanBuilder {
         }))
     }
 
+    /// Apply window functions, extending the schema with their results
+    pub fn window(&self, window_expr: Vec<Expr>) -> Result<Self> {
+        let mut fields = self.plan.schema().fields().clone();
+        fields.extend(exprlist_to_fields(&window_expr, self.plan.schema())?);
+
+        Ok(Self::from(&LogicalPlan::Window {
+            input: Arc::new(self.plan.clone()),
+            window_expr,
+            schema: SchemaRef::new(Schema::new(fields)),
+        }))
+    }
+
+    /// Apply a join with `right` on the equality of the pairs of columns of
+    /// `left_keys` and `right_keys`
+    pub fn join(
+        &self,
+        right: &LogicalPlan,
+        join_type: JoinType,
+        left_keys: &[&str],
+        right_keys: &[&str],
+    ) -> Result<Self> {
+        if left_keys.len() != right_keys.len() {
+            return Err(DataFusionError::Plan(
+                "left_keys and right_keys were not the same length".to_string(),
+            ));
+        }
+        let left_schema = self.plan.schema();
+        let right_schema = right.schema();
+        for (l, r) in left_keys.iter().zip(right_keys.iter()) {
+            left_schema.index_of(l)?;
+            right_schema.index_of(r)?;
+        }
+
+        // the rows of an input may not match when the join preserves the other input
+        let left_nullable = join_type == JoinType::Right || join_type == JoinType::Full;
+        let right_nullable = join_type == JoinType::Left || join_type == JoinType::Full;
+        let is_shared_key = |name: &str| {
+            left_keys
+                .iter()
+                .zip(right_keys.iter())
+                .any(|(l, r)| l == r && *r == name)
+        };
+        let mut fields: Vec<Field> = left_schema
+            .fields()
+            .iter()
+            .map(|f| {
+                if !is_shared_key(f.name()) {
+                    let nullable = f.is_nullable() || left_nullable;
+                    return Ok(Field::new(f.name(), f.data_type().clone(), nullable));
+                }
+                // a shared key is output once, from the input whose rows are
+                // all preserved
+                let right_field = right_schema.field_with_name(f.name())?;
+                Ok(match join_type {
+                    JoinType::Right => right_field.clone(),
+                    JoinType::Full => Field::new(
+                        f.name(),
+                        f.data_type().clone(),
+                        f.is_nullable() && right_field.is_nullable(),
+                    ),
+                    JoinType::Inner | JoinType::Left => f.clone(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        for f in right_schema.fields() {
+            if is_shared_key(f.name()) {
+                continue;
+            }
+            if left_schema.field_with_name(f.name()).is_ok() {
+                return Err(DataFusionError::Plan(format!(
+                    "The column {} appears in both inputs of the join",
+                    f.name()
+                )));
+            }
+            let nullable = f.is_nullable() || right_nullable;
+            fields.push(Field::new(f.name(), f.data_type().clone(), nullable));
+        }
+
+        Ok(Self::from(&LogicalPlan::Join {
+            left: Arc::new(self.plan.clone()),
+            right: Arc::new(right.clone()),
+            on: left_keys
+                .iter()
+                .zip(right_keys.iter())
+                .map(|(l, r)| (l.to_string(), r.to_string()))
+                .collect(),
+            join_type,
+            schema: SchemaRef::new(Schema::new(fields)),
+        }))
+    }
+
     /// Create an expression to represent the explanation of the plan
     pub fn explain(&self, verbose: bool) -> Result<Self> {
         let stringified_plans = vec![StringifiedPlan::new(
@@ -1374,6 +1971,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn plan_builder_truth_value_predicates() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("active", DataType::Boolean, true)]);
+        let scan = LogicalPlanBuilder::scan("default", "t.csv", &schema, None)?;
+
+        let cases = vec![
+            (
+                col("active").is_true(),
+                "#active IsNotDistinctFrom Boolean(true)",
+            ),
+            (
+                col("active").is_not_true(),
+                "#active IsDistinctFrom Boolean(true)",
+            ),
+            (
+                col("active").is_false(),
+                "#active IsNotDistinctFrom Boolean(false)",
+            ),
+            (col("active").is_unknown(), "#active IS NULL"),
+        ];
+        for (expr, expected) in cases {
+            let plan = scan.filter(expr)?.build()?;
+            let expected =
+                format!("Filter: {}\n  TableScan: t.csv projection=None", expected);
+            assert_eq!(expected, format!("{:?}", plan));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_builder_is_distinct_from() -> Result<()> {
+        let plan = LogicalPlanBuilder::scan(
+            "default",
+            "employee.csv",
+            &employee_schema(),
+            Some(vec![0, 3]),
+        )?
+        .filter(col("state").is_distinct_from(lit("CO")))?
+        .filter(col("id").is_not_distinct_from(lit(1)))?
+        .build()?;
+
+        let expected = "Filter: #id IsNotDistinctFrom Int32(1)\
+        \n  Filter: #state IsDistinctFrom Utf8(\"CO\")\
+        \n    TableScan: employee.csv projection=Some([0, 3])";
+
+        assert_eq!(expected, format!("{:?}", plan));
+
+        Ok(())
+    }
+
     #[test]
     fn plan_builder_csv() -> Result<()> {
         let plan = LogicalPlanBuilder::scan_csv(
@@ -1418,6 +2066,65 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn plan_builder_aggregate_grouping_sets() -> Result<()> {
+        let plan = LogicalPlanBuilder::scan(
+            "default",
+            "employee.csv",
+            &employee_schema(),
+            Some(vec![3, 4]),
+        )?
+        .aggregate(
+            vec![grouping_sets(vec![
+                vec![col("state")],
+                vec![col("salary")],
+                vec![],
+            ])],
+            vec![count(col("state"))],
+        )?
+        .build()?;
+
+        let expected = "Aggregate: groupBy=[[GROUPING SETS ((#state), (#salary), ())]], aggr=[[COUNT(#state)]]\
+        \n  TableScan: employee.csv projection=Some([3, 4])";
+
+        assert_eq!(expected, format!("{:?}", plan));
+
+        // the grouping columns are the union of the sets, and are nullable
+        let schema = plan.schema();
+        assert_eq!(3, schema.fields().len());
+        assert!(schema.field_with_name("state")?.is_nullable());
+        assert!(schema.field_with_name("salary")?.is_nullable());
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_builder_try_cast() -> Result<()> {
+        let try_cast = Expr::TryCast {
+            expr: Box::new(col("state")),
+            data_type: DataType::Int32,
+        };
+        let plan = LogicalPlanBuilder::scan(
+            "default",
+            "employee.csv",
+            &employee_schema(),
+            Some(vec![3]),
+        )?
+        .project(vec![try_cast])?
+        .build()?;
+
+        let expected = "Projection: TRY_CAST(#state AS Int32)\
+        \n  TableScan: employee.csv projection=Some([3])";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        // the values that cannot be converted are NULL
+        let field = plan.schema().field_with_name("TRY_CAST(state AS Int32)")?;
+        assert_eq!(&DataType::Int32, field.data_type());
+        assert!(field.is_nullable());
+
+        Ok(())
+    }
+
     #[test]
     fn plan_builder_sort() -> Result<()> {
         let plan = LogicalPlanBuilder::scan(
@@ -1496,6 +2203,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn aggregate_names_include_filter() -> Result<()> {
+        let count_filtered = |predicate: Expr| Expr::AggregateFunction {
+            fun: aggregates::AggregateFunction::Count,
+            distinct: false,
+            args: vec![col("id")],
+            filter: Some(Box::new(predicate)),
+        };
+        let plan = LogicalPlanBuilder::scan(
+            "default",
+            "employee.csv",
+            &employee_schema(),
+            Some(vec![0, 3, 4]),
+        )?
+        // counts that only differ by their filter have different names
+        .aggregate(
+            vec![col("state")],
+            vec![
+                count_filtered(col("salary").gt(lit(1000))),
+                count_filtered(col("salary").lt(lit(1000))),
+            ],
+        )?
+        .build()?;
+
+        let names: Vec<&String> =
+            plan.schema().fields().iter().map(|f| f.name()).collect();
+        assert_eq!(
+            vec![
+                "state",
+                "COUNT(id) FILTER (WHERE salary Gt Int32(1000))",
+                "COUNT(id) FILTER (WHERE salary Lt Int32(1000))",
+            ],
+            names
+        );
+        Ok(())
+    }
+
     fn employee_schema() -> Schema {
         Schema::new(vec![
             Field::new("id", DataType::Int32, false),
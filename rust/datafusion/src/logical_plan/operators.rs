@@ -52,6 +52,10 @@ pub enum Operator {
     Like,
     /// Does not match a wildcard pattern
     NotLike,
+    /// Expressions are not equal, treating two nulls as equal
+    IsDistinctFrom,
+    /// Expressions are equal, treating two nulls as equal
+    IsNotDistinctFrom,
 }
 
 impl fmt::Display for Operator {
@@ -72,6 +76,8 @@ impl fmt::Display for Operator {
             Operator::Or => "OR",
             Operator::Like => "LIKE",
             Operator::NotLike => "NOT LIKE",
+            Operator::IsDistinctFrom => "IS DISTINCT FROM",
+            Operator::IsNotDistinctFrom => "IS NOT DISTINCT FROM",
         };
         write!(f, "{}", display)
     }
@@ -201,7 +201,7 @@ fn analyze_plan(plan: &LogicalPlan, depth: usize) -> Result<AnalysisResult> {
             Ok(result)
         }
         LogicalPlan::Sort { input, .. } => analyze_plan(&input, depth + 1),
-        LogicalPlan::Limit { input, .. } => {
+        LogicalPlan::Limit { input, .. } | LogicalPlan::Offset { input, .. } => {
             let mut result = analyze_plan(&input, depth + 1)?;
 
             // collect all columns that break at this depth
@@ -309,10 +309,18 @@ fn optimize_plan(
         // all other nodes: Add any additional columns used by
         // expressions in this node to the list of required columns
         LogicalPlan::Limit { .. }
+        | LogicalPlan::Offset { .. }
+        | LogicalPlan::Settings { .. }
+        | LogicalPlan::Analyze { .. }
+        | LogicalPlan::Window { .. }
+        | LogicalPlan::Join { .. }
         | LogicalPlan::Filter { .. }
         | LogicalPlan::EmptyRelation { .. }
         | LogicalPlan::Sort { .. }
         | LogicalPlan::CreateExternalTable { .. }
+        | LogicalPlan::DropTable { .. }
+        | LogicalPlan::Update { .. }
+        | LogicalPlan::DescribeTable { .. }
         | LogicalPlan::Extension { .. } => {
             let expr = utils::expressions(plan);
             // collect all required columns by this plan
@@ -23,7 +23,9 @@ use arrow::datatypes::{Schema, SchemaRef};
 
 use super::optimizer::OptimizerRule;
 use crate::error::{DataFusionError, Result};
-use crate::logical_plan::{Expr, LogicalPlan, PlanType, StringifiedPlan};
+use crate::logical_plan::{
+    Expr, LogicalPlan, LogicalPlanBuilder, PlanType, StringifiedPlan, TableSource,
+};
 
 /// Recursively walk a list of expression trees, collecting the unique set of column
 /// names referenced in the expression
@@ -46,10 +48,12 @@ pub fn expr_to_column_names(expr: &Expr, accum: &mut HashSet<String>) -> Result<
             accum.insert(name.clone());
             Ok(())
         }
-        Expr::ScalarVariable(var_names) => {
+        Expr::ScalarVariable(_, var_names) => {
             accum.insert(var_names.join("."));
             Ok(())
         }
+        // not a column of the input
+        Expr::OuterColumn(_, _) => Ok(()),
         Expr::Literal(_) => {
             // not needed
             Ok(())
@@ -63,15 +67,41 @@ pub fn expr_to_column_names(expr: &Expr, accum: &mut HashSet<String>) -> Result<
             Ok(())
         }
         Expr::Cast { expr, .. } => expr_to_column_names(expr, accum),
+        Expr::TryCast { expr, .. } => expr_to_column_names(expr, accum),
         Expr::Sort { expr, .. } => expr_to_column_names(expr, accum),
-        Expr::AggregateFunction { args, .. } => exprlist_to_column_names(args, accum),
+        Expr::AggregateFunction { args, filter, .. } => {
+            exprlist_to_column_names(args, accum)?;
+            match filter {
+                Some(filter) => expr_to_column_names(filter, accum),
+                None => Ok(()),
+            }
+        }
         Expr::AggregateUDF { args, .. } => exprlist_to_column_names(args, accum),
+        Expr::WindowFunction {
+            args,
+            partition_by,
+            order_by,
+            ..
+        } => {
+            exprlist_to_column_names(args, accum)?;
+            exprlist_to_column_names(partition_by, accum)?;
+            exprlist_to_column_names(order_by, accum)
+        }
         Expr::ScalarFunction { args, .. } => exprlist_to_column_names(args, accum),
         Expr::ScalarUDF { args, .. } => exprlist_to_column_names(args, accum),
         Expr::Wildcard => Err(DataFusionError::Internal(
             "Wildcard expressions are not valid in a logical query plan".to_owned(),
         )),
         Expr::Nested(e) => expr_to_column_names(e, accum),
+        // the subquery's columns are resolved against its own input
+        Expr::Exists { .. } => Ok(()),
+        Expr::InSubquery { expr, .. } => expr_to_column_names(expr, accum),
+        Expr::GroupingSet(sets) => {
+            for set in sets {
+                exprlist_to_column_names(set, accum)?;
+            }
+            Ok(())
+        }
     }
 }
 
@@ -118,7 +148,15 @@ pub fn expressions(plan: &LogicalPlan) -> Vec<Expr> {
             result.extend(aggr_expr.clone());
             result
         }
+        LogicalPlan::Window { window_expr, .. } => window_expr.clone(),
+        LogicalPlan::Join { on, .. } => on
+            .iter()
+            .flat_map(|(l, r)| vec![Expr::Column(l.clone()), Expr::Column(r.clone())])
+            .collect(),
         LogicalPlan::Sort { expr, .. } => expr.clone(),
+        LogicalPlan::Update { assignments, .. } => {
+            assignments.iter().map(|(_, value)| value.clone()).collect()
+        }
         LogicalPlan::Extension { node } => node.expressions(),
         // plans without expressions
         LogicalPlan::TableScan { .. }
@@ -127,7 +165,12 @@ pub fn expressions(plan: &LogicalPlan) -> Vec<Expr> {
         | LogicalPlan::CsvScan { .. }
         | LogicalPlan::EmptyRelation { .. }
         | LogicalPlan::Limit { .. }
+        | LogicalPlan::Offset { .. }
+        | LogicalPlan::Settings { .. }
+        | LogicalPlan::Analyze { .. }
         | LogicalPlan::CreateExternalTable { .. }
+        | LogicalPlan::DropTable { .. }
+        | LogicalPlan::DescribeTable { .. }
         | LogicalPlan::Explain { .. } => vec![],
     }
 }
@@ -138,8 +181,14 @@ pub fn inputs(plan: &LogicalPlan) -> Vec<&LogicalPlan> {
         LogicalPlan::Projection { input, .. } => vec![input],
         LogicalPlan::Filter { input, .. } => vec![input],
         LogicalPlan::Aggregate { input, .. } => vec![input],
+        LogicalPlan::Window { input, .. } => vec![input],
+        LogicalPlan::Join { left, right, .. } => vec![left, right],
         LogicalPlan::Sort { input, .. } => vec![input],
         LogicalPlan::Limit { input, .. } => vec![input],
+        LogicalPlan::Offset { input, .. } => vec![input],
+        LogicalPlan::Settings { input, .. } => vec![input],
+        LogicalPlan::Analyze { input, .. } => vec![input],
+        LogicalPlan::Update { input, .. } => vec![input],
         LogicalPlan::Extension { node } => node.inputs(),
         // plans without inputs
         LogicalPlan::TableScan { .. }
@@ -148,6 +197,8 @@ pub fn inputs(plan: &LogicalPlan) -> Vec<&LogicalPlan> {
         | LogicalPlan::CsvScan { .. }
         | LogicalPlan::EmptyRelation { .. }
         | LogicalPlan::CreateExternalTable { .. }
+        | LogicalPlan::DropTable { .. }
+        | LogicalPlan::DescribeTable { .. }
         | LogicalPlan::Explain { .. } => vec![],
     }
 }
@@ -176,6 +227,16 @@ pub fn from_plan(
             input: Arc::new(inputs[0].clone()),
             schema: schema.clone(),
         }),
+        LogicalPlan::Window { .. } => LogicalPlanBuilder::from(&inputs[0])
+            .window(expr.clone())?
+            .build(),
+        LogicalPlan::Join { on, join_type, .. } => {
+            let (left_keys, right_keys): (Vec<_>, Vec<_>) =
+                on.iter().map(|(l, r)| (l.as_str(), r.as_str())).unzip();
+            LogicalPlanBuilder::from(&inputs[0])
+                .join(&inputs[1], *join_type, &left_keys, &right_keys)?
+                .build()
+        }
         LogicalPlan::Sort { .. } => Ok(LogicalPlan::Sort {
             expr: expr.clone(),
             input: Arc::new(inputs[0].clone()),
@@ -184,6 +245,36 @@ pub fn from_plan(
             n: *n,
             input: Arc::new(inputs[0].clone()),
         }),
+        LogicalPlan::Offset { n, .. } => Ok(LogicalPlan::Offset {
+            n: *n,
+            input: Arc::new(inputs[0].clone()),
+        }),
+        LogicalPlan::Settings { settings, .. } => Ok(LogicalPlan::Settings {
+            settings: settings.clone(),
+            input: Arc::new(inputs[0].clone()),
+        }),
+        LogicalPlan::Analyze {
+            verbose, schema, ..
+        } => Ok(LogicalPlan::Analyze {
+            verbose: *verbose,
+            input: Arc::new(inputs[0].clone()),
+            schema: schema.clone(),
+        }),
+        LogicalPlan::Update {
+            table_name,
+            assignments,
+            schema,
+            ..
+        } => Ok(LogicalPlan::Update {
+            table_name: table_name.clone(),
+            assignments: assignments
+                .iter()
+                .zip(expr.iter())
+                .map(|((column, _), value)| (column.clone(), value.clone()))
+                .collect(),
+            input: Arc::new(inputs[0].clone()),
+            schema: schema.clone(),
+        }),
         LogicalPlan::Extension { node } => Ok(LogicalPlan::Extension {
             node: node.from_template(expr, inputs),
         }),
@@ -193,6 +284,8 @@ pub fn from_plan(
         | LogicalPlan::ParquetScan { .. }
         | LogicalPlan::CsvScan { .. }
         | LogicalPlan::CreateExternalTable { .. }
+        | LogicalPlan::DropTable { .. }
+        | LogicalPlan::DescribeTable { .. }
         | LogicalPlan::Explain { .. } => Ok(plan.clone()),
     }
 }
@@ -206,19 +299,37 @@ pub fn expr_sub_expressions(expr: &Expr) -> Result<Vec<&Expr>> {
         Expr::IsNotNull(e) => Ok(vec![e]),
         Expr::ScalarFunction { args, .. } => Ok(args.iter().collect()),
         Expr::ScalarUDF { args, .. } => Ok(args.iter().collect()),
-        Expr::AggregateFunction { args, .. } => Ok(args.iter().collect()),
+        Expr::AggregateFunction { args, filter, .. } => Ok(args
+            .iter()
+            .chain(filter.iter().map(|f| f.as_ref()))
+            .collect()),
         Expr::AggregateUDF { args, .. } => Ok(args.iter().collect()),
+        Expr::WindowFunction {
+            args,
+            partition_by,
+            order_by,
+            ..
+        } => Ok(args
+            .iter()
+            .chain(partition_by.iter())
+            .chain(order_by.iter())
+            .collect()),
         Expr::Cast { expr, .. } => Ok(vec![expr]),
+        Expr::TryCast { expr, .. } => Ok(vec![expr]),
         Expr::Column(_) => Ok(vec![]),
         Expr::Alias(expr, ..) => Ok(vec![expr]),
         Expr::Literal(_) => Ok(vec![]),
-        Expr::ScalarVariable(_) => Ok(vec![]),
+        Expr::ScalarVariable(_, _) => Ok(vec![]),
+        Expr::OuterColumn(_, _) => Ok(vec![]),
         Expr::Not(expr) => Ok(vec![expr]),
         Expr::Sort { expr, .. } => Ok(vec![expr]),
         Expr::Wildcard { .. } => Err(DataFusionError::Internal(
             "Wildcard expressions are not valid in a logical query plan".to_owned(),
         )),
         Expr::Nested(expr) => Ok(vec![expr]),
+        Expr::Exists { .. } => Ok(vec![]),
+        Expr::InSubquery { expr, .. } => Ok(vec![expr]),
+        Expr::GroupingSet(sets) => Ok(sets.iter().flatten().collect()),
     }
 }
 
@@ -241,26 +352,56 @@ pub fn rewrite_expression(expr: &Expr, expressions: &Vec<Expr>) -> Result<Expr>
             fun: fun.clone(),
             args: expressions.clone(),
         }),
-        Expr::AggregateFunction { fun, distinct, .. } => Ok(Expr::AggregateFunction {
+        Expr::AggregateFunction {
+            fun,
+            distinct,
+            args,
+            filter,
+        } => Ok(Expr::AggregateFunction {
             fun: fun.clone(),
-            args: expressions.clone(),
+            args: expressions[0..args.len()].to_vec(),
             distinct: *distinct,
+            filter: filter
+                .as_ref()
+                .map(|_| Box::new(expressions[args.len()].clone())),
         }),
         Expr::AggregateUDF { fun, .. } => Ok(Expr::AggregateUDF {
             fun: fun.clone(),
             args: expressions.clone(),
         }),
+        Expr::WindowFunction {
+            fun,
+            args,
+            partition_by,
+            window_frame,
+            ..
+        } => {
+            let partition_start = args.len();
+            let order_start = partition_start + partition_by.len();
+            Ok(Expr::WindowFunction {
+                fun: fun.clone(),
+                args: expressions[..partition_start].to_vec(),
+                partition_by: expressions[partition_start..order_start].to_vec(),
+                order_by: expressions[order_start..].to_vec(),
+                window_frame: window_frame.clone(),
+            })
+        }
         Expr::Cast { data_type, .. } => Ok(Expr::Cast {
             expr: Box::new(expressions[0].clone()),
             data_type: data_type.clone(),
         }),
+        Expr::TryCast { data_type, .. } => Ok(Expr::TryCast {
+            expr: Box::new(expressions[0].clone()),
+            data_type: data_type.clone(),
+        }),
         Expr::Alias(_, alias) => {
             Ok(Expr::Alias(Box::new(expressions[0].clone()), alias.clone()))
         }
         Expr::Not(_) => Ok(Expr::Not(Box::new(expressions[0].clone()))),
         Expr::Column(_) => Ok(expr.clone()),
         Expr::Literal(_) => Ok(expr.clone()),
-        Expr::ScalarVariable(_) => Ok(expr.clone()),
+        Expr::ScalarVariable(_, _) => Ok(expr.clone()),
+        Expr::OuterColumn(_, _) => Ok(expr.clone()),
         Expr::Sort {
             asc, nulls_first, ..
         } => Ok(Expr::Sort {
@@ -272,6 +413,182 @@ pub fn rewrite_expression(expr: &Expr, expressions: &Vec<Expr>) -> Result<Expr>
             "Wildcard expressions are not valid in a logical query plan".to_owned(),
         )),
         Expr::Nested(_) => Ok(Expr::Nested(Box::new(expressions[0].clone()))),
+        Expr::Exists { .. } => Ok(expr.clone()),
+        Expr::InSubquery {
+            subquery, negated, ..
+        } => Ok(Expr::InSubquery {
+            expr: Box::new(expressions[0].clone()),
+            subquery: subquery.clone(),
+            negated: *negated,
+        }),
+        Expr::GroupingSet(sets) => {
+            let mut offset = 0;
+            let sets = sets
+                .iter()
+                .map(|set| {
+                    let set_expressions =
+                        expressions[offset..offset + set.len()].to_vec();
+                    offset += set.len();
+                    set_expressions
+                })
+                .collect();
+            Ok(Expr::GroupingSet(sets))
+        }
+    }
+}
+
+/// Returns the columns of the scanned tables, as `(table, column)` pairs, that
+/// the plan reads to compute its output, including the columns read by its
+/// filters, join keys and subqueries. File scans are named by their path, and
+/// scans of table providers passed by reference have an empty name.
+pub fn referenced_columns(plan: &LogicalPlan) -> Result<HashSet<(String, String)>> {
+    let required = plan
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect();
+    let mut accum = HashSet::new();
+    collect_referenced_columns(plan, &required, &mut accum)?;
+    Ok(accum)
+}
+
+/// Collects the columns of the scanned tables that `plan` reads to compute the
+/// `required` columns of its output
+fn collect_referenced_columns(
+    plan: &LogicalPlan,
+    required: &HashSet<String>,
+    accum: &mut HashSet<(String, String)>,
+) -> Result<()> {
+    let exprs = expressions(plan);
+
+    // the columns of the input referenced by correlated subqueries
+    let mut input_required = HashSet::new();
+    for subquery in find_subqueries(&exprs) {
+        accum.extend(referenced_columns(&subquery)?);
+        collect_outer_columns(&subquery, &mut input_required);
+    }
+
+    match plan {
+        LogicalPlan::TableScan {
+            source,
+            projected_schema,
+            ..
+        } => {
+            let table = match source {
+                TableSource::FromContext(name) => name.as_str(),
+                TableSource::FromProvider(_) => "",
+            };
+            add_scan_columns(table, projected_schema, required, accum);
+            Ok(())
+        }
+        LogicalPlan::CsvScan {
+            path,
+            projected_schema,
+            ..
+        }
+        | LogicalPlan::ParquetScan {
+            path,
+            projected_schema,
+            ..
+        } => {
+            add_scan_columns(path, projected_schema, required, accum);
+            Ok(())
+        }
+        LogicalPlan::InMemoryScan {
+            projected_schema, ..
+        } => {
+            add_scan_columns("", projected_schema, required, accum);
+            Ok(())
+        }
+        // only the expressions that compute a required column are read
+        LogicalPlan::Projection { input, .. } | LogicalPlan::Window { input, .. } => {
+            for name in required {
+                if input.schema().field_with_name(name).is_ok() {
+                    input_required.insert(name.clone());
+                }
+            }
+            for e in &exprs {
+                if required.contains(&e.name(input.schema())?) {
+                    expr_to_column_names(e, &mut input_required)?;
+                }
+            }
+            collect_referenced_columns(input, &input_required, accum)
+        }
+        // every grouping expression determines the output
+        LogicalPlan::Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            ..
+        } => {
+            exprlist_to_column_names(group_expr, &mut input_required)?;
+            for e in aggr_expr {
+                if required.contains(&e.name(input.schema())?) {
+                    expr_to_column_names(e, &mut input_required)?;
+                }
+            }
+            collect_referenced_columns(input, &input_required, accum)
+        }
+        // the other plans pass the columns of their inputs through, and read the
+        // columns of their expressions, such as filters, sorts and join keys
+        _ => {
+            input_required.extend(required.iter().cloned());
+            exprlist_to_column_names(&exprs, &mut input_required)?;
+            for input in inputs(plan) {
+                collect_referenced_columns(input, &input_required, accum)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn add_scan_columns(
+    table: &str,
+    schema: &Schema,
+    required: &HashSet<String>,
+    accum: &mut HashSet<(String, String)>,
+) {
+    for field in schema.fields() {
+        if required.contains(field.name()) {
+            accum.insert((table.to_string(), field.name().clone()));
+        }
+    }
+}
+
+/// Calls `f` on `expr` and on all its sub-expressions
+fn walk_expr(expr: &Expr, f: &mut dyn FnMut(&Expr)) {
+    f(expr);
+    for e in expr_sub_expressions(expr).unwrap_or_default() {
+        walk_expr(e, f);
+    }
+}
+
+/// Returns the plans of the subqueries in `exprs`
+fn find_subqueries(exprs: &[Expr]) -> Vec<Arc<LogicalPlan>> {
+    let mut subqueries = vec![];
+    for expr in exprs {
+        walk_expr(expr, &mut |e| match e {
+            Expr::Exists { subquery, .. } | Expr::InSubquery { subquery, .. } => {
+                subqueries.push(subquery.clone())
+            }
+            _ => {}
+        });
+    }
+    subqueries
+}
+
+/// Collects the columns of the enclosing query that the subquery `plan` references
+fn collect_outer_columns(plan: &LogicalPlan, accum: &mut HashSet<String>) {
+    for expr in expressions(plan) {
+        walk_expr(&expr, &mut |e| {
+            if let Expr::OuterColumn(name, _) = e {
+                accum.insert(name.clone());
+            }
+        });
+    }
+    for input in inputs(plan) {
+        collect_outer_columns(input, accum);
     }
 }
 
@@ -35,7 +35,7 @@ use crate::error::{DataFusionError, Result};
 use crate::physical_plan::distinct_expressions;
 use crate::physical_plan::expressions;
 use arrow::datatypes::{DataType, Schema};
-use expressions::{avg_return_type, sum_return_type};
+use expressions::{avg_return_type, sum_return_type, StatsType};
 use std::{fmt, str::FromStr, sync::Arc};
 
 /// the implementation of an aggregate function
@@ -60,12 +60,40 @@ pub enum AggregateFunction {
     Max,
     /// avg
     Avg,
+    /// sample standard deviation
+    Stddev,
+    /// population standard deviation
+    StddevPop,
+    /// sample variance
+    Variance,
+    /// population variance
+    VariancePop,
+    /// the list of all values of a group
+    ArrayAgg,
+    /// whether all the values of a group are true
+    BoolAnd,
+    /// whether any of the values of a group is true
+    BoolOr,
+    /// the middle value of a group
+    Median,
+    /// the value at a given percentile of a group, interpolated between values
+    ApproxPercentileCont,
 }
 
 impl fmt::Display for AggregateFunction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // uppercase of the debug.
-        write!(f, "{}", format!("{:?}", self).to_uppercase())
+        match self {
+            AggregateFunction::StddevPop => write!(f, "STDDEV_POP"),
+            AggregateFunction::VariancePop => write!(f, "VAR_POP"),
+            AggregateFunction::ArrayAgg => write!(f, "ARRAY_AGG"),
+            AggregateFunction::BoolAnd => write!(f, "BOOL_AND"),
+            AggregateFunction::BoolOr => write!(f, "BOOL_OR"),
+            AggregateFunction::ApproxPercentileCont => {
+                write!(f, "APPROX_PERCENTILE_CONT")
+            }
+            // uppercase of the debug.
+            _ => write!(f, "{}", format!("{:?}", self).to_uppercase()),
+        }
     }
 }
 
@@ -78,6 +106,15 @@ impl FromStr for AggregateFunction {
             "COUNT" => AggregateFunction::Count,
             "AVG" => AggregateFunction::Avg,
             "SUM" => AggregateFunction::Sum,
+            "STDDEV" | "STDDEV_SAMP" => AggregateFunction::Stddev,
+            "STDDEV_POP" => AggregateFunction::StddevPop,
+            "VARIANCE" | "VAR_SAMP" => AggregateFunction::Variance,
+            "VAR_POP" => AggregateFunction::VariancePop,
+            "ARRAY_AGG" => AggregateFunction::ArrayAgg,
+            "BOOL_AND" => AggregateFunction::BoolAnd,
+            "BOOL_OR" => AggregateFunction::BoolOr,
+            "MEDIAN" => AggregateFunction::Median,
+            "APPROX_PERCENTILE_CONT" => AggregateFunction::ApproxPercentileCont,
             _ => {
                 return Err(DataFusionError::Plan(format!(
                     "There is no built-in function named {}",
@@ -104,6 +141,14 @@ pub fn return_type(
         AggregateFunction::Max | AggregateFunction::Min => Ok(arg_types[0].clone()),
         AggregateFunction::Sum => sum_return_type(&arg_types[0]),
         AggregateFunction::Avg => avg_return_type(&arg_types[0]),
+        AggregateFunction::Stddev
+        | AggregateFunction::StddevPop
+        | AggregateFunction::Variance
+        | AggregateFunction::VariancePop
+        | AggregateFunction::Median
+        | AggregateFunction::ApproxPercentileCont => Ok(DataType::Float64),
+        AggregateFunction::ArrayAgg => Ok(DataType::List(Box::new(arg_types[0].clone()))),
+        AggregateFunction::BoolAnd | AggregateFunction::BoolOr => Ok(DataType::Boolean),
     }
 }
 
@@ -160,6 +205,41 @@ pub fn create_aggregate_expr(
                 "AVG(DISTINCT) aggregations are not available".to_string(),
             ));
         }
+        (AggregateFunction::Stddev, false) => Arc::new(
+            expressions::Variance::new_stddev(arg, name, StatsType::Sample),
+        ),
+        (AggregateFunction::StddevPop, false) => Arc::new(
+            expressions::Variance::new_stddev(arg, name, StatsType::Population),
+        ),
+        (AggregateFunction::Variance, false) => {
+            Arc::new(expressions::Variance::new(arg, name, StatsType::Sample))
+        }
+        (AggregateFunction::VariancePop, false) => {
+            Arc::new(expressions::Variance::new(arg, name, StatsType::Population))
+        }
+        (AggregateFunction::Stddev, true)
+        | (AggregateFunction::StddevPop, true)
+        | (AggregateFunction::Variance, true)
+        | (AggregateFunction::VariancePop, true) => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "{}(DISTINCT) aggregations are not available",
+                fun
+            )));
+        }
+        (AggregateFunction::BoolAnd, _) => {
+            Arc::new(expressions::BoolAggregate::new_and(arg, name))
+        }
+        (AggregateFunction::BoolOr, _) => {
+            Arc::new(expressions::BoolAggregate::new_or(arg, name))
+        }
+        (AggregateFunction::ArrayAgg, _)
+        | (AggregateFunction::Median, _)
+        | (AggregateFunction::ApproxPercentileCont, _) => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "{} aggregations are not available",
+                fun
+            )));
+        }
     })
 }
 
@@ -180,15 +260,29 @@ static NUMERICS: &'static [DataType] = &[
 fn signature(fun: &AggregateFunction) -> Signature {
     // note: the physical expression must accept the type returned by this function or the execution panics.
     match fun {
-        AggregateFunction::Count => Signature::Any(1),
+        AggregateFunction::Count | AggregateFunction::ArrayAgg => Signature::Any(1),
         AggregateFunction::Min | AggregateFunction::Max => {
             let mut valid = vec![DataType::Utf8, DataType::LargeUtf8];
             valid.extend_from_slice(NUMERICS);
             Signature::Uniform(1, valid)
         }
-        AggregateFunction::Avg | AggregateFunction::Sum => {
-            Signature::Uniform(1, NUMERICS.to_vec())
+        AggregateFunction::BoolAnd | AggregateFunction::BoolOr => {
+            Signature::Uniform(1, vec![DataType::Boolean])
         }
+        AggregateFunction::Avg
+        | AggregateFunction::Sum
+        | AggregateFunction::Stddev
+        | AggregateFunction::StddevPop
+        | AggregateFunction::Variance
+        | AggregateFunction::VariancePop
+        | AggregateFunction::Median => Signature::Uniform(1, NUMERICS.to_vec()),
+        // the percentile is a float
+        AggregateFunction::ApproxPercentileCont => Signature::OneOf(
+            NUMERICS
+                .iter()
+                .map(|t| Signature::Exact(vec![t.clone(), DataType::Float64]))
+                .collect(),
+        ),
     }
 }
 
@@ -241,6 +335,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_variance_return_type() -> Result<()> {
+        for name in &["stddev", "STDDEV_POP", "var_samp", "VAR_POP", "variance"] {
+            let fun = AggregateFunction::from_str(name)?;
+            let observed = return_type(&fun, &vec![DataType::Int32])?;
+            assert_eq!(DataType::Float64, observed);
+            assert!(return_type(&fun, &vec![DataType::Utf8]).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_agg_return_type() -> Result<()> {
+        let observed = return_type(&AggregateFunction::ArrayAgg, &vec![DataType::Utf8])?;
+        assert_eq!(DataType::List(Box::new(DataType::Utf8)), observed);
+
+        let observed = return_type(&AggregateFunction::ArrayAgg, &vec![DataType::Int64])?;
+        assert_eq!(DataType::List(Box::new(DataType::Int64)), observed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bool_and_or_return_type() -> Result<()> {
+        for name in &["bool_and", "BOOL_OR"] {
+            let fun = AggregateFunction::from_str(name)?;
+            let observed = return_type(&fun, &vec![DataType::Boolean])?;
+            assert_eq!(DataType::Boolean, observed);
+            assert!(return_type(&fun, &vec![DataType::Utf8]).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_median_percentile_return_type() -> Result<()> {
+        let observed = return_type(&AggregateFunction::Median, &vec![DataType::Int32])?;
+        assert_eq!(DataType::Float64, observed);
+
+        let fun = AggregateFunction::from_str("approx_percentile_cont")?;
+        let observed = return_type(&fun, &vec![DataType::UInt8, DataType::Float64])?;
+        assert_eq!(DataType::Float64, observed);
+        assert!(return_type(&fun, &vec![DataType::Float64]).is_err());
+        assert!(return_type(&fun, &vec![DataType::Utf8, DataType::Float64]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_avg_no_utf8() -> Result<()> {
         let observed = return_type(&AggregateFunction::Avg, &vec![DataType::Utf8]);
@@ -0,0 +1,46 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Conditional expressions
+
+use crate::error::{DataFusionError, Result};
+use arrow::array::{Array, ArrayRef, UInt32Builder};
+use arrow::compute::kernels::concat::concat;
+use arrow::compute::kernels::take::take;
+
+/// returns, for each row, the value of the first argument that is not null, or
+/// null when all the arguments are. The arguments must be of the same type.
+pub fn coalesce(args: &[ArrayRef]) -> Result<ArrayRef> {
+    if args.is_empty() {
+        return Err(DataFusionError::Internal(
+            "Coalesce was called with 0 arguments. It requires at least one.".to_string(),
+        ));
+    }
+    let len = args[0].len();
+
+    // the values of all the arguments, one after the other, from which the
+    // value of each row is taken
+    let values = concat(args)?;
+    let mut indices = UInt32Builder::new(len);
+    for index in 0..len {
+        match args.iter().position(|arg| arg.is_valid(index)) {
+            Some(arg) => indices.append_value((arg * len + index) as u32)?,
+            None => indices.append_null()?,
+        }
+    }
+    Ok(take(&values, &indices.finish(), None)?)
+}
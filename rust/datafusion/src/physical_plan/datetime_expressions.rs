@@ -25,7 +25,7 @@ use arrow::{
     buffer::Buffer,
     datatypes::{DataType, TimeUnit, ToByteSlice},
 };
-use chrono::{prelude::*, LocalResult};
+use chrono::{prelude::*, Duration, LocalResult};
 
 #[inline]
 /// Accepts a string in RFC3339 / ISO8601 standard format and some
@@ -78,7 +78,7 @@ use chrono::{prelude::*, LocalResult};
 /// the system timezone is set to Americas/New_York (UTC-5) the
 /// timestamp will be interpreted as though it were
 /// `1997-01-31T09:26:56.123-05:00`
-fn string_to_timestamp_nanos(s: &str) -> Result<i64> {
+pub(crate) fn string_to_timestamp_nanos(s: &str) -> Result<i64> {
     // Fast path:  RFC3339 timestamp (with a T)
     // Example: 2020-09-08T13:42:29.190855Z
     if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
@@ -205,6 +205,63 @@ pub fn to_timestamp(args: &[ArrayRef]) -> Result<TimestampNanosecondArray> {
     Ok(TimestampNanosecondArray::from(Arc::new(data)))
 }
 
+/// truncates the timestamp `value`, in nanoseconds, to the start of the `second`,
+/// `minute`, `hour`, `day`, `week`, `month` or `year` it belongs to
+fn date_trunc_single(granularity: &str, value: i64) -> Result<i64> {
+    let datetime = NaiveDateTime::from_timestamp(value.div_euclid(1_000_000_000), 0);
+    let date = datetime.date();
+    let truncated = match &*granularity.to_lowercase() {
+        "second" => datetime,
+        "minute" => date.and_hms(datetime.hour(), datetime.minute(), 0),
+        "hour" => date.and_hms(datetime.hour(), 0, 0),
+        "day" => date.and_hms(0, 0, 0),
+        "week" => (date - Duration::days(date.weekday().num_days_from_monday() as i64))
+            .and_hms(0, 0, 0),
+        "month" => NaiveDate::from_ymd(date.year(), date.month(), 1).and_hms(0, 0, 0),
+        "year" => NaiveDate::from_ymd(date.year(), 1, 1).and_hms(0, 0, 0),
+        _ => {
+            return Err(DataFusionError::Execution(format!(
+                "Unsupported date_trunc granularity '{}'",
+                granularity
+            )))
+        }
+    };
+    Ok(truncated.timestamp_nanos())
+}
+
+/// truncates the timestamps of `args[1]` to the granularity named by `args[0]`
+pub fn date_trunc(args: &[ArrayRef]) -> Result<TimestampNanosecondArray> {
+    let granularities =
+        args[0]
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "could not cast date_trunc granularity to StringArray"
+                ))
+            })?;
+    let timestamps = args[1]
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .ok_or_else(|| {
+            DataFusionError::Internal(format!(
+                "could not cast date_trunc input to TimestampNanosecondArray"
+            ))
+        })?;
+
+    let result = (0..timestamps.len())
+        .map(|i| {
+            if granularities.is_null(i) || timestamps.is_null(i) {
+                Ok(None)
+            } else {
+                date_trunc_single(granularities.value(i), timestamps.value(i)).map(Some)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(TimestampNanosecondArray::from_opt_vec(result, None))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -403,4 +460,41 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn date_trunc_granularities() -> Result<()> {
+        // 2020-09-08T13:42:29.190855Z, a Tuesday
+        let value = 1599572549190855000;
+        let cases = vec![
+            ("second", "2020-09-08T13:42:29Z"),
+            ("minute", "2020-09-08T13:42:00Z"),
+            ("hour", "2020-09-08T13:00:00Z"),
+            ("day", "2020-09-08T00:00:00Z"),
+            ("WEEK", "2020-09-07T00:00:00Z"),
+            ("month", "2020-09-01T00:00:00Z"),
+            ("year", "2020-01-01T00:00:00Z"),
+        ];
+        for (granularity, expected) in cases {
+            assert_eq!(
+                parse_timestamp(expected)?,
+                date_trunc_single(granularity, value)?,
+                "granularity {}",
+                granularity
+            );
+        }
+        assert!(date_trunc_single("decade", value).is_err());
+
+        let granularities: ArrayRef =
+            Arc::new(StringArray::from(vec![Some("day"), Some("day"), None]));
+        let timestamps: ArrayRef = Arc::new(TimestampNanosecondArray::from_opt_vec(
+            vec![Some(value), None, Some(value)],
+            None,
+        ));
+        let truncated = date_trunc(&[granularities, timestamps])?;
+        assert_eq!(truncated.len(), 3);
+        assert_eq!(truncated.value(0), parse_timestamp("2020-09-08T00:00:00Z")?);
+        assert!(truncated.is_null(1));
+        assert!(truncated.is_null(2));
+        Ok(())
+    }
 }
@@ -26,9 +26,12 @@ use crate::logical_plan::Operator;
 use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
 use crate::scalar::ScalarValue;
 use arrow::array::{
-    Float32Builder, Float64Builder, Int16Builder, Int32Builder, Int64Builder,
-    Int8Builder, LargeStringArray, StringBuilder, UInt16Builder, UInt32Builder,
-    UInt64Builder, UInt8Builder,
+    Array, BinaryArray, BooleanBuilder, Date32Array, Date64Array, Float32Builder,
+    Float64Builder, Int16Builder, Int32Builder, Int64Builder, Int8Builder,
+    LargeBinaryArray, LargeStringArray, StringBuilder, Time32MillisecondArray,
+    Time32SecondArray, Time64MicrosecondArray, Time64NanosecondArray,
+    TimestampMicrosecondArray, TimestampMillisecondArray, TimestampSecondArray,
+    UInt16Builder, UInt32Builder, UInt64Builder, UInt8Builder,
 };
 use arrow::compute;
 use arrow::compute::kernels;
@@ -477,6 +480,275 @@ impl Accumulator for AvgAccumulator {
     }
 }
 
+/// Whether a statistic is computed over a sample or over the whole population
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsType {
+    /// Sample statistic, e.g. with a denominator of `n - 1` for the variance
+    Sample,
+    /// Population statistic, e.g. with a denominator of `n` for the variance
+    Population,
+}
+
+/// VARIANCE and STDDEV aggregate expressions
+#[derive(Debug)]
+pub struct Variance {
+    name: String,
+    expr: Arc<dyn PhysicalExpr>,
+    stats_type: StatsType,
+    stddev: bool,
+}
+
+impl Variance {
+    /// Create a new VARIANCE aggregate function
+    pub fn new(expr: Arc<dyn PhysicalExpr>, name: String, stats_type: StatsType) -> Self {
+        Self {
+            name,
+            expr,
+            stats_type,
+            stddev: false,
+        }
+    }
+
+    /// Create a new STDDEV aggregate function, the square root of the variance
+    pub fn new_stddev(
+        expr: Arc<dyn PhysicalExpr>,
+        name: String,
+        stats_type: StatsType,
+    ) -> Self {
+        Self {
+            name,
+            expr,
+            stats_type,
+            stddev: true,
+        }
+    }
+}
+
+impl AggregateExpr for Variance {
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(vec![
+            Field::new(
+                &format_state_name(&self.name, "count"),
+                DataType::UInt64,
+                true,
+            ),
+            Field::new(
+                &format_state_name(&self.name, "mean"),
+                DataType::Float64,
+                true,
+            ),
+            Field::new(
+                &format_state_name(&self.name, "m2"),
+                DataType::Float64,
+                true,
+            ),
+        ])
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(VarianceAccumulator {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            stats_type: self.stats_type,
+            stddev: self.stddev,
+        }))
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+}
+
+/// An accumulator to compute the variance, using Welford's online algorithm
+#[derive(Debug)]
+struct VarianceAccumulator {
+    count: u64,
+    mean: f64,
+    // sum of the squared differences from the mean
+    m2: f64,
+    stats_type: StatsType,
+    stddev: bool,
+}
+
+/// Converts a numeric scalar to f64
+fn scalar_to_f64(value: &ScalarValue) -> Result<Option<f64>> {
+    Ok(match value {
+        ScalarValue::Int8(v) => v.map(|v| v as f64),
+        ScalarValue::Int16(v) => v.map(|v| v as f64),
+        ScalarValue::Int32(v) => v.map(|v| v as f64),
+        ScalarValue::Int64(v) => v.map(|v| v as f64),
+        ScalarValue::UInt8(v) => v.map(|v| v as f64),
+        ScalarValue::UInt16(v) => v.map(|v| v as f64),
+        ScalarValue::UInt32(v) => v.map(|v| v as f64),
+        ScalarValue::UInt64(v) => v.map(|v| v as f64),
+        ScalarValue::Float32(v) => v.map(|v| v as f64),
+        ScalarValue::Float64(v) => *v,
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Cannot compute the variance of {:?}",
+                other
+            )))
+        }
+    })
+}
+
+impl Accumulator for VarianceAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![
+            ScalarValue::from(self.count),
+            ScalarValue::from(self.mean),
+            ScalarValue::from(self.m2),
+        ])
+    }
+
+    fn update(&mut self, values: &Vec<ScalarValue>) -> Result<()> {
+        if let Some(value) = scalar_to_f64(&values[0])? {
+            self.count += 1;
+            let delta = value - self.mean;
+            self.mean += delta / self.count as f64;
+            self.m2 += delta * (value - self.mean);
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &Vec<ScalarValue>) -> Result<()> {
+        let count = match &states[0] {
+            ScalarValue::UInt64(Some(c)) => *c,
+            _ => unreachable!(),
+        };
+        let mean = scalar_to_f64(&states[1])?.unwrap_or(0.0);
+        let m2 = scalar_to_f64(&states[2])?.unwrap_or(0.0);
+        if count == 0 {
+            return Ok(());
+        }
+
+        // combine the two partial results (Chan et al.)
+        let total = self.count + count;
+        let delta = mean - self.mean;
+        self.mean += delta * count as f64 / total as f64;
+        self.m2 += m2 + delta * delta * (self.count * count) as f64 / total as f64;
+        self.count = total;
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        let denominator = match self.stats_type {
+            StatsType::Sample => self.count.saturating_sub(1),
+            StatsType::Population => self.count,
+        };
+        if denominator == 0 {
+            return Ok(ScalarValue::Float64(None));
+        }
+        let variance = self.m2 / denominator as f64;
+        Ok(ScalarValue::from(if self.stddev {
+            variance.sqrt()
+        } else {
+            variance
+        }))
+    }
+}
+
+/// BOOL_AND and BOOL_OR aggregate expressions
+#[derive(Debug)]
+pub struct BoolAggregate {
+    name: String,
+    expr: Arc<dyn PhysicalExpr>,
+    and: bool,
+}
+
+impl BoolAggregate {
+    /// Create a new BOOL_AND aggregate function, true when all the values are
+    pub fn new_and(expr: Arc<dyn PhysicalExpr>, name: String) -> Self {
+        Self {
+            name,
+            expr,
+            and: true,
+        }
+    }
+
+    /// Create a new BOOL_OR aggregate function, true when any of the values is
+    pub fn new_or(expr: Arc<dyn PhysicalExpr>, name: String) -> Self {
+        Self {
+            name,
+            expr,
+            and: false,
+        }
+    }
+}
+
+impl AggregateExpr for BoolAggregate {
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Boolean, true))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        let state_name = if self.and { "bool_and" } else { "bool_or" };
+        Ok(vec![Field::new(
+            &format_state_name(&self.name, state_name),
+            DataType::Boolean,
+            true,
+        )])
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(BoolAccumulator {
+            value: None,
+            and: self.and,
+        }))
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+}
+
+/// An accumulator of the conjunction or disjunction of the non-null values
+#[derive(Debug)]
+struct BoolAccumulator {
+    // `None` until a non-null value is seen
+    value: Option<bool>,
+    and: bool,
+}
+
+impl Accumulator for BoolAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Boolean(self.value)])
+    }
+
+    fn update(&mut self, values: &Vec<ScalarValue>) -> Result<()> {
+        let value = match &values[0] {
+            ScalarValue::Boolean(value) => *value,
+            other => {
+                return Err(DataFusionError::Internal(format!(
+                    "BOOL_AND and BOOL_OR expect boolean values, found {:?}",
+                    other
+                )))
+            }
+        };
+        if let Some(value) = value {
+            self.value = Some(match self.value {
+                Some(current) if self.and => current && value,
+                Some(current) => current || value,
+                None => value,
+            });
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &Vec<ScalarValue>) -> Result<()> {
+        self.update(states)
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Boolean(self.value))
+    }
+}
+
 /// MAX aggregate expression
 #[derive(Debug)]
 pub struct Max {
@@ -1042,6 +1314,32 @@ macro_rules! binary_array_op {
     }};
 }
 
+/// Compare a pair of arrays of the same type row by row, where two nulls are
+/// not distinct from each other and a null is distinct from any value
+macro_rules! distinct_from_op {
+    ($LEFT:expr, $RIGHT:expr, $DISTINCT:expr, $DT:ident) => {{
+        let ll = $LEFT
+            .as_any()
+            .downcast_ref::<$DT>()
+            .expect("distinct_from_op failed to downcast array");
+        let rr = $RIGHT
+            .as_any()
+            .downcast_ref::<$DT>()
+            .expect("distinct_from_op failed to downcast array");
+        let result = (0..ll.len())
+            .map(|i| {
+                let not_distinct = match (ll.is_null(i), rr.is_null(i)) {
+                    (true, true) => true,
+                    (false, false) => ll.value(i) == rr.value(i),
+                    _ => false,
+                };
+                Some(not_distinct != $DISTINCT)
+            })
+            .collect::<Vec<_>>();
+        Ok(Arc::new(BooleanArray::from(result)) as ArrayRef)
+    }};
+}
+
 /// Invoke a boolean kernel on a pair of arrays
 macro_rules! boolean_op {
     ($LEFT:expr, $RIGHT:expr, $OP:ident) => {{
@@ -1217,7 +1515,10 @@ fn common_binary_type(
             _ => None,
         },
         // logical equality operators have their own rules, and always return a boolean
-        Operator::Eq | Operator::NotEq => eq_coercion(lhs_type, rhs_type),
+        Operator::Eq
+        | Operator::NotEq
+        | Operator::IsDistinctFrom
+        | Operator::IsNotDistinctFrom => eq_coercion(lhs_type, rhs_type),
         // "like" operators operate on strings and always return a boolean
         Operator::Like | Operator::NotLike => string_coercion(lhs_type, rhs_type),
         // order-comparison operators have their own rules
@@ -1266,6 +1567,8 @@ pub fn binary_operator_data_type(
         // operators that return a boolean
         Operator::Eq
         | Operator::NotEq
+        | Operator::IsDistinctFrom
+        | Operator::IsNotDistinctFrom
         | Operator::And
         | Operator::Or
         | Operator::Like
@@ -1313,7 +1616,14 @@ impl PhysicalExpr for BinaryExpr {
     }
 
     fn nullable(&self, input_schema: &Schema) -> Result<bool> {
-        Ok(self.left.nullable(input_schema)? || self.right.nullable(input_schema)?)
+        match self.op {
+            // null-safe comparisons always produce a value
+            Operator::IsDistinctFrom | Operator::IsNotDistinctFrom => Ok(false),
+            _ => {
+                Ok(self.left.nullable(input_schema)?
+                    || self.right.nullable(input_schema)?)
+            }
+        }
     }
 
     fn evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
@@ -1337,6 +1647,8 @@ impl PhysicalExpr for BinaryExpr {
             Operator::GtEq => binary_array_op!(left, right, gt_eq),
             Operator::Eq => binary_array_op!(left, right, eq),
             Operator::NotEq => binary_array_op!(left, right, neq),
+            Operator::IsDistinctFrom => is_distinct_from(&left, &right, true),
+            Operator::IsNotDistinctFrom => is_distinct_from(&left, &right, false),
             Operator::Plus => binary_primitive_array_op!(left, right, add),
             Operator::Minus => binary_primitive_array_op!(left, right, subtract),
             Operator::Multiply => binary_primitive_array_op!(left, right, multiply),
@@ -1372,6 +1684,65 @@ impl PhysicalExpr for BinaryExpr {
     }
 }
 
+/// Null-safe comparison of two arrays of the same type: two nulls are not
+/// distinct from each other, and a null is distinct from any value. The
+/// result has no nulls; `distinct` selects which of the two answers is `true`.
+fn is_distinct_from(
+    left: &ArrayRef,
+    right: &ArrayRef,
+    distinct: bool,
+) -> Result<ArrayRef> {
+    match left.data_type() {
+        DataType::Boolean => distinct_from_op!(left, right, distinct, BooleanArray),
+        DataType::Int8 => distinct_from_op!(left, right, distinct, Int8Array),
+        DataType::Int16 => distinct_from_op!(left, right, distinct, Int16Array),
+        DataType::Int32 => distinct_from_op!(left, right, distinct, Int32Array),
+        DataType::Int64 => distinct_from_op!(left, right, distinct, Int64Array),
+        DataType::UInt8 => distinct_from_op!(left, right, distinct, UInt8Array),
+        DataType::UInt16 => distinct_from_op!(left, right, distinct, UInt16Array),
+        DataType::UInt32 => distinct_from_op!(left, right, distinct, UInt32Array),
+        DataType::UInt64 => distinct_from_op!(left, right, distinct, UInt64Array),
+        DataType::Float32 => distinct_from_op!(left, right, distinct, Float32Array),
+        DataType::Float64 => distinct_from_op!(left, right, distinct, Float64Array),
+        DataType::Utf8 => distinct_from_op!(left, right, distinct, StringArray),
+        DataType::LargeUtf8 => distinct_from_op!(left, right, distinct, LargeStringArray),
+        DataType::Binary => distinct_from_op!(left, right, distinct, BinaryArray),
+        DataType::LargeBinary => {
+            distinct_from_op!(left, right, distinct, LargeBinaryArray)
+        }
+        DataType::Date32(_) => distinct_from_op!(left, right, distinct, Date32Array),
+        DataType::Date64(_) => distinct_from_op!(left, right, distinct, Date64Array),
+        DataType::Time32(TimeUnit::Second) => {
+            distinct_from_op!(left, right, distinct, Time32SecondArray)
+        }
+        DataType::Time32(TimeUnit::Millisecond) => {
+            distinct_from_op!(left, right, distinct, Time32MillisecondArray)
+        }
+        DataType::Time64(TimeUnit::Microsecond) => {
+            distinct_from_op!(left, right, distinct, Time64MicrosecondArray)
+        }
+        DataType::Time64(TimeUnit::Nanosecond) => {
+            distinct_from_op!(left, right, distinct, Time64NanosecondArray)
+        }
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            distinct_from_op!(left, right, distinct, TimestampSecondArray)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            distinct_from_op!(left, right, distinct, TimestampMillisecondArray)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            distinct_from_op!(left, right, distinct, TimestampMicrosecondArray)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            distinct_from_op!(left, right, distinct, TimestampNanosecondArray)
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "Unsupported data type {:?} for IS DISTINCT FROM",
+            other
+        ))),
+    }
+}
+
 /// Create a binary expression whose arguments are correctly coerced.
 /// This function errors if it is not possible to coerce the arguments
 /// to computational types supported by the operator.
@@ -1631,6 +2002,9 @@ impl PhysicalExpr for Literal {
 
     fn evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
         match &self.value {
+            ScalarValue::Boolean(value) => {
+                build_literal_array!(batch, BooleanBuilder, *value)
+            }
             ScalarValue::Int8(value) => build_literal_array!(batch, Int8Builder, *value),
             ScalarValue::Int16(value) => {
                 build_literal_array!(batch, Int16Builder, *value)
@@ -1749,6 +2123,76 @@ mod tests {
         Ok(())
     }
 
+    // evaluates "a IS DISTINCT FROM b" and "a IS NOT DISTINCT FROM b" over two
+    // nullable columns and compares the first result with `expected`
+    fn assert_distinct_from(
+        data_type: DataType,
+        a: ArrayRef,
+        b: ArrayRef,
+        expected: Vec<bool>,
+    ) -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", data_type.clone(), true),
+            Field::new("b", data_type, true),
+        ]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![a, b])?;
+
+        for (op, distinct) in &[
+            (Operator::IsDistinctFrom, true),
+            (Operator::IsNotDistinctFrom, false),
+        ] {
+            let expr = binary(col("a"), op.clone(), col("b"), &schema)?;
+            assert_eq!(expr.nullable(&schema)?, false);
+            let result = expr.evaluate(&batch)?;
+            let result = result
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .expect("failed to downcast to BooleanArray");
+            assert_eq!(result.null_count(), 0);
+            for i in 0..expected.len() {
+                assert_eq!(result.value(i), expected[i] == *distinct);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn binary_is_distinct_from() -> Result<()> {
+        assert_distinct_from(
+            DataType::Int32,
+            Arc::new(Int32Array::from(vec![Some(1), None, None, Some(4)])),
+            Arc::new(Int32Array::from(vec![Some(1), None, Some(3), Some(5)])),
+            vec![false, false, true, true],
+        )?;
+        assert_distinct_from(
+            DataType::Boolean,
+            Arc::new(BooleanArray::from(vec![
+                Some(true),
+                None,
+                None,
+                Some(false),
+            ])),
+            Arc::new(BooleanArray::from(vec![
+                Some(true),
+                None,
+                Some(false),
+                Some(true),
+            ])),
+            vec![false, false, true, true],
+        )?;
+        assert_distinct_from(
+            DataType::Utf8,
+            Arc::new(StringArray::from(vec![
+                Some("a"),
+                None,
+                Some("b"),
+                Some("c"),
+            ])),
+            Arc::new(StringArray::from(vec![Some("a"), None, None, Some("d")])),
+            vec![false, false, true, true],
+        )
+    }
+
     #[test]
     fn binary_nested() -> Result<()> {
         let schema = Schema::new(vec![
@@ -2172,6 +2616,100 @@ mod tests {
         )
     }
 
+    #[test]
+    fn variance_i32() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![
+            Some(1),
+            Some(2),
+            None,
+            Some(3),
+            Some(4),
+            Some(5),
+        ]));
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![a])?;
+
+        let cases = vec![
+            (
+                Variance::new(col("a"), "v".to_string(), StatsType::Sample),
+                2.5,
+            ),
+            (
+                Variance::new(col("a"), "v".to_string(), StatsType::Population),
+                2.0,
+            ),
+            (
+                Variance::new_stddev(col("a"), "v".to_string(), StatsType::Sample),
+                2.5_f64.sqrt(),
+            ),
+            (
+                Variance::new_stddev(col("a"), "v".to_string(), StatsType::Population),
+                2.0_f64.sqrt(),
+            ),
+        ];
+        for (agg, expected) in cases {
+            let actual = aggregate(&batch, Arc::new(agg))?;
+            assert_eq!(ScalarValue::from(expected), actual);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn variance_merge() -> Result<()> {
+        let agg = Variance::new(col("a"), "v".to_string(), StatsType::Population);
+        let mut left = agg.create_accumulator()?;
+        left.update(&vec![ScalarValue::from(1_f64)])?;
+        left.update(&vec![ScalarValue::from(2_f64)])?;
+        let mut right = agg.create_accumulator()?;
+        right.update(&vec![ScalarValue::from(3_f64)])?;
+        right.update(&vec![ScalarValue::from(4_f64)])?;
+        right.update(&vec![ScalarValue::from(5_f64)])?;
+
+        left.merge(&right.state()?)?;
+        assert_eq!(ScalarValue::from(2_f64), left.evaluate()?);
+        Ok(())
+    }
+
+    #[test]
+    fn variance_of_single_value() -> Result<()> {
+        let agg = Variance::new(col("a"), "v".to_string(), StatsType::Sample);
+        let mut acc = agg.create_accumulator()?;
+        acc.update(&vec![ScalarValue::from(1_f64)])?;
+        assert_eq!(ScalarValue::Float64(None), acc.evaluate()?);
+        Ok(())
+    }
+
+    #[test]
+    fn bool_and_or() -> Result<()> {
+        let a: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            None,
+            Some(false),
+            Some(true),
+        ]));
+        let schema = Schema::new(vec![Field::new("a", DataType::Boolean, true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![a])?;
+
+        let agg = BoolAggregate::new_and(col("a"), "b".to_string());
+        assert_eq!(
+            ScalarValue::Boolean(Some(false)),
+            aggregate(&batch, Arc::new(agg))?
+        );
+        let agg = BoolAggregate::new_or(col("a"), "b".to_string());
+        assert_eq!(
+            ScalarValue::Boolean(Some(true)),
+            aggregate(&batch, Arc::new(agg))?
+        );
+
+        // no value at all
+        let agg = BoolAggregate::new_or(col("a"), "b".to_string());
+        assert_eq!(
+            ScalarValue::Boolean(None),
+            agg.create_accumulator()?.evaluate()?
+        );
+        Ok(())
+    }
+
     #[test]
     fn max_i32() -> Result<()> {
         let a: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5]));
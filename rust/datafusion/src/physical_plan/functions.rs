@@ -35,9 +35,13 @@ use super::{
 };
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::array_expressions;
+use crate::physical_plan::conditional_expressions;
 use crate::physical_plan::datetime_expressions;
+use crate::physical_plan::expressions;
 use crate::physical_plan::math_expressions;
+use crate::physical_plan::regex_expressions;
 use crate::physical_plan::string_expressions;
+use crate::scalar::ScalarValue;
 use arrow::{
     array::ArrayRef,
     compute::kernels::length::length,
@@ -45,6 +49,7 @@ use arrow::{
     datatypes::{DataType, Schema},
     record_batch::RecordBatch,
 };
+use chrono::Utc;
 use fmt::{Debug, Formatter};
 use std::{fmt, str::FromStr, sync::Arc};
 
@@ -66,6 +71,9 @@ pub enum Signature {
     Exact(Vec<DataType>),
     /// fixed number of arguments of arbitrary types
     Any(usize),
+    /// any of the signatures, such as for a function with optional arguments
+    // The first signature the arguments can be coerced to is used
+    OneOf(Vec<Signature>),
 }
 
 /// Scalar function
@@ -113,20 +121,48 @@ pub enum BuiltinScalarFunction {
     Abs,
     /// signum
     Signum,
+    /// power
+    Power,
     /// length
     Length,
     /// concat
     Concat,
+    /// trim
+    Trim,
+    /// ltrim
+    Ltrim,
+    /// rtrim
+    Rtrim,
+    /// strpos
+    Strpos,
+    /// overlay
+    Overlay,
+    /// regexp_match
+    RegexpMatch,
+    /// regexp_replace
+    RegexpReplace,
     /// to_timestamp
     ToTimestamp,
+    /// date_trunc
+    DateTrunc,
+    /// now
+    Now,
     /// construct an array from columns
     Array,
+    /// coalesce
+    Coalesce,
 }
 
 impl fmt::Display for BuiltinScalarFunction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // lowercase of the debug.
-        write!(f, "{}", format!("{:?}", self).to_lowercase())
+        match self {
+            BuiltinScalarFunction::ToTimestamp => write!(f, "to_timestamp"),
+            BuiltinScalarFunction::DateTrunc => write!(f, "date_trunc"),
+            BuiltinScalarFunction::RegexpMatch => write!(f, "regexp_match"),
+            BuiltinScalarFunction::RegexpReplace => write!(f, "regexp_replace"),
+            // lowercase of the debug.
+            _ => write!(f, "{}", format!("{:?}", self).to_lowercase()),
+        }
     }
 }
 
@@ -151,10 +187,21 @@ impl FromStr for BuiltinScalarFunction {
             "truc" => BuiltinScalarFunction::Trunc,
             "abs" => BuiltinScalarFunction::Abs,
             "signum" => BuiltinScalarFunction::Signum,
+            "power" => BuiltinScalarFunction::Power,
             "length" => BuiltinScalarFunction::Length,
             "concat" => BuiltinScalarFunction::Concat,
+            "trim" => BuiltinScalarFunction::Trim,
+            "ltrim" => BuiltinScalarFunction::Ltrim,
+            "rtrim" => BuiltinScalarFunction::Rtrim,
+            "strpos" => BuiltinScalarFunction::Strpos,
+            "overlay" => BuiltinScalarFunction::Overlay,
+            "regexp_match" => BuiltinScalarFunction::RegexpMatch,
+            "regexp_replace" => BuiltinScalarFunction::RegexpReplace,
             "to_timestamp" => BuiltinScalarFunction::ToTimestamp,
+            "date_trunc" => BuiltinScalarFunction::DateTrunc,
+            "now" => BuiltinScalarFunction::Now,
             "array" => BuiltinScalarFunction::Array,
+            "coalesce" => BuiltinScalarFunction::Coalesce,
             _ => {
                 return Err(DataFusionError::Plan(format!(
                     "There is no built-in function named {}",
@@ -176,9 +223,10 @@ pub fn return_type(
     // verify that this is a valid set of data types for this function
     data_types(&arg_types, &signature(fun))?;
 
-    if arg_types.len() == 0 {
+    if arg_types.len() == 0 && *fun != BuiltinScalarFunction::Now {
         // functions currently cannot be evaluated without arguments, as they can't
-        // know the number of rows to return.
+        // know the number of rows to return. `now` is the exception, as it is
+        // evaluated once, when the physical plan is created.
         return Err(DataFusionError::Plan(
             format!("Function '{}' requires at least one argument", fun).to_string(),
         ));
@@ -199,13 +247,34 @@ pub fn return_type(
             }
         }),
         BuiltinScalarFunction::Concat => Ok(DataType::Utf8),
-        BuiltinScalarFunction::ToTimestamp => {
+        BuiltinScalarFunction::Trim
+        | BuiltinScalarFunction::Ltrim
+        | BuiltinScalarFunction::Rtrim => {
+            if arg_types.len() > 2 {
+                return Err(DataFusionError::Plan(format!(
+                    "Function '{}' accepts at most two arguments, found {}",
+                    fun,
+                    arg_types.len()
+                )));
+            }
+            Ok(DataType::Utf8)
+        }
+        BuiltinScalarFunction::Strpos => Ok(DataType::Int32),
+        BuiltinScalarFunction::Overlay => Ok(DataType::Utf8),
+        BuiltinScalarFunction::RegexpMatch => {
+            Ok(DataType::List(Box::new(DataType::Utf8)))
+        }
+        BuiltinScalarFunction::RegexpReplace => Ok(DataType::Utf8),
+        BuiltinScalarFunction::ToTimestamp
+        | BuiltinScalarFunction::DateTrunc
+        | BuiltinScalarFunction::Now => {
             Ok(DataType::Timestamp(TimeUnit::Nanosecond, None))
         }
         BuiltinScalarFunction::Array => Ok(DataType::FixedSizeList(
             Box::new(arg_types[0].clone()),
             arg_types.len() as i32,
         )),
+        BuiltinScalarFunction::Coalesce => Ok(arg_types[0].clone()),
         _ => Ok(DataType::Float64),
     }
 }
@@ -235,14 +304,50 @@ pub fn create_physical_expr(
         BuiltinScalarFunction::Trunc => math_expressions::trunc,
         BuiltinScalarFunction::Abs => math_expressions::abs,
         BuiltinScalarFunction::Signum => math_expressions::signum,
+        BuiltinScalarFunction::Power => math_expressions::power,
         BuiltinScalarFunction::Length => |args| Ok(length(args[0].as_ref())?),
         BuiltinScalarFunction::Concat => {
             |args| Ok(Arc::new(string_expressions::concatenate(args)?))
         }
+        BuiltinScalarFunction::Trim => {
+            |args| Ok(Arc::new(string_expressions::trim(args)?))
+        }
+        BuiltinScalarFunction::Ltrim => {
+            |args| Ok(Arc::new(string_expressions::ltrim(args)?))
+        }
+        BuiltinScalarFunction::Rtrim => {
+            |args| Ok(Arc::new(string_expressions::rtrim(args)?))
+        }
+        BuiltinScalarFunction::Strpos => {
+            |args| Ok(Arc::new(string_expressions::strpos(args)?))
+        }
+        BuiltinScalarFunction::Overlay => {
+            |args| Ok(Arc::new(string_expressions::overlay(args)?))
+        }
+        BuiltinScalarFunction::RegexpMatch => {
+            |args| Ok(Arc::new(regex_expressions::regexp_match(args)?))
+        }
+        BuiltinScalarFunction::RegexpReplace => {
+            |args| Ok(Arc::new(regex_expressions::regexp_replace(args)?))
+        }
         BuiltinScalarFunction::ToTimestamp => {
             |args| Ok(Arc::new(datetime_expressions::to_timestamp(args)?))
         }
+        BuiltinScalarFunction::DateTrunc => {
+            |args| Ok(Arc::new(datetime_expressions::date_trunc(args)?))
+        }
+        // the current time is taken once, so that it is the same for all the rows
+        BuiltinScalarFunction::Now => {
+            return expressions::cast(
+                expressions::lit(ScalarValue::Int64(Some(Utc::now().timestamp_nanos()))),
+                input_schema,
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+            )
+        }
         BuiltinScalarFunction::Array => |args| Ok(array_expressions::array(args)?),
+        BuiltinScalarFunction::Coalesce => {
+            |args| Ok(conditional_expressions::coalesce(args)?)
+        }
     });
     // coerce
     let args = coerce(args, input_schema, &signature(fun))?;
@@ -270,10 +375,49 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
             Signature::Uniform(1, vec![DataType::Utf8, DataType::LargeUtf8])
         }
         BuiltinScalarFunction::Concat => Signature::Variadic(vec![DataType::Utf8]),
+        // the string to trim, optionally followed by the characters to remove
+        BuiltinScalarFunction::Trim
+        | BuiltinScalarFunction::Ltrim
+        | BuiltinScalarFunction::Rtrim => Signature::Variadic(vec![DataType::Utf8]),
+        // the string and the substring to search for
+        BuiltinScalarFunction::Strpos => {
+            Signature::Exact(vec![DataType::Utf8, DataType::Utf8])
+        }
+        // the string, the replacement, its position and optionally the number of
+        // characters it replaces
+        BuiltinScalarFunction::Overlay => Signature::OneOf(vec![
+            Signature::Exact(vec![DataType::Utf8, DataType::Utf8, DataType::Int64]),
+            Signature::Exact(vec![
+                DataType::Utf8,
+                DataType::Utf8,
+                DataType::Int64,
+                DataType::Int64,
+            ]),
+        ]),
+        // the string and the pattern
+        BuiltinScalarFunction::RegexpMatch => {
+            Signature::Exact(vec![DataType::Utf8, DataType::Utf8])
+        }
+        // the string, the pattern, the replacement and optionally the flags
+        BuiltinScalarFunction::RegexpReplace => Signature::OneOf(vec![
+            Signature::Uniform(3, vec![DataType::Utf8]),
+            Signature::Uniform(4, vec![DataType::Utf8]),
+        ]),
         BuiltinScalarFunction::ToTimestamp => Signature::Uniform(1, vec![DataType::Utf8]),
+        // the granularity, such as 'day', and the timestamp to truncate
+        BuiltinScalarFunction::DateTrunc => Signature::Exact(vec![
+            DataType::Utf8,
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+        ]),
+        BuiltinScalarFunction::Now => Signature::Exact(vec![]),
+        // the base and the exponent
+        BuiltinScalarFunction::Power => {
+            Signature::Exact(vec![DataType::Float64, DataType::Float64])
+        }
         BuiltinScalarFunction::Array => {
             Signature::Variadic(array_expressions::SUPPORTED_ARRAY_TYPES.to_vec())
         }
+        BuiltinScalarFunction::Coalesce => Signature::VariadicEqual,
         // math expressions expect 1 argument of type f64 or f32
         // priority is given to f64 because e.g. `sqrt(1i32)` is in IR (real numbers) and thus we
         // return the best approximation for it (in f64).
@@ -360,9 +504,16 @@ impl PhysicalExpr for ScalarFunctionExpr {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{error::Result, physical_plan::expressions::lit, scalar::ScalarValue};
+    use crate::{
+        error::Result,
+        physical_plan::expressions::{col, lit},
+        scalar::ScalarValue,
+    };
     use arrow::{
-        array::{ArrayRef, FixedSizeListArray, Float64Array, Int32Array, StringArray},
+        array::{
+            ArrayRef, FixedSizeListArray, Float64Array, Int32Array, StringArray,
+            TimestampNanosecondArray,
+        },
         datatypes::Field,
         record_batch::RecordBatch,
     };
@@ -439,6 +590,201 @@ mod tests {
         test_concat(ScalarValue::Utf8(Some("aa".to_string())), "aaaa")
     }
 
+    fn test_trim(
+        fun: BuiltinScalarFunction,
+        args: Vec<ScalarValue>,
+        expected: &str,
+    ) -> Result<()> {
+        // any type works here: we evaluate against a literal of `value`
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let columns: Vec<ArrayRef> = vec![Arc::new(Int32Array::from(vec![1]))];
+
+        let args = args.into_iter().map(lit).collect();
+        let expr = create_physical_expr(&fun, &args, &schema)?;
+
+        // type is correct
+        assert_eq!(expr.data_type(&schema)?, DataType::Utf8);
+
+        // evaluate works
+        let result =
+            expr.evaluate(&RecordBatch::try_new(Arc::new(schema.clone()), columns)?)?;
+
+        // downcast works
+        let result = result.as_any().downcast_ref::<StringArray>().unwrap();
+
+        // value is correct
+        assert_eq!(result.value(0), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_utf8() -> Result<()> {
+        let value = ScalarValue::Utf8(Some("  xxaxx  ".to_string()));
+        let chars = ScalarValue::Utf8(Some(" x".to_string()));
+        test_trim(BuiltinScalarFunction::Trim, vec![value.clone()], "xxaxx")?;
+        test_trim(BuiltinScalarFunction::Ltrim, vec![value.clone()], "xxaxx  ")?;
+        test_trim(BuiltinScalarFunction::Rtrim, vec![value.clone()], "  xxaxx")?;
+        test_trim(
+            BuiltinScalarFunction::Trim,
+            vec![value.clone(), chars.clone()],
+            "a",
+        )?;
+        test_trim(
+            BuiltinScalarFunction::Ltrim,
+            vec![value.clone(), chars.clone()],
+            "axx  ",
+        )?;
+        test_trim(BuiltinScalarFunction::Rtrim, vec![value, chars], "  xxa")
+    }
+
+    fn test_string_function(
+        fun: BuiltinScalarFunction,
+        args: Vec<ScalarValue>,
+        expected: &str,
+    ) -> Result<()> {
+        // any type works here: we evaluate against literals
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let columns: Vec<ArrayRef> = vec![Arc::new(Int32Array::from(vec![1]))];
+
+        let args = args.into_iter().map(lit).collect();
+        let expr = create_physical_expr(&fun, &args, &schema)?;
+
+        let result =
+            expr.evaluate(&RecordBatch::try_new(Arc::new(schema.clone()), columns)?)?;
+        let result = ScalarValue::try_from_array(&result, 0)?;
+        assert_eq!(format!("{:?}", result), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_power() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let columns: Vec<ArrayRef> = vec![Arc::new(Int32Array::from(vec![1]))];
+
+        // integers are coerced to f64
+        let args = vec![
+            lit(ScalarValue::Int64(Some(2))),
+            lit(ScalarValue::Int32(Some(3))),
+        ];
+        let expr = create_physical_expr(&BuiltinScalarFunction::Power, &args, &schema)?;
+        assert_eq!(expr.data_type(&schema)?, DataType::Float64);
+
+        let result = expr.evaluate(&RecordBatch::try_new(Arc::new(schema), columns)?)?;
+        let result = result.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(result.value(0), 8.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_strpos_and_overlay() -> Result<()> {
+        let utf8 = |s: &str| ScalarValue::Utf8(Some(s.to_string()));
+        test_string_function(
+            BuiltinScalarFunction::Strpos,
+            vec![utf8("héllo"), utf8("llo")],
+            "Int32(3)",
+        )?;
+        test_string_function(
+            BuiltinScalarFunction::Strpos,
+            vec![utf8("hello"), utf8("x")],
+            "Int32(0)",
+        )?;
+        test_string_function(
+            BuiltinScalarFunction::Overlay,
+            vec![utf8("Txxxxas"), utf8("hom"), ScalarValue::Int64(Some(2))],
+            "Utf8(\"Thomxas\")",
+        )?;
+        test_string_function(
+            BuiltinScalarFunction::Overlay,
+            vec![
+                utf8("Txxxxas"),
+                utf8("hom"),
+                ScalarValue::Int64(Some(2)),
+                ScalarValue::Int64(Some(4)),
+            ],
+            "Utf8(\"Thomas\")",
+        )?;
+
+        // the position must be an integer
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let args = vec![
+            lit(utf8("a")),
+            lit(utf8("b")),
+            lit(ScalarValue::Float64(Some(1.0))),
+        ];
+        assert!(
+            create_physical_expr(&BuiltinScalarFunction::Overlay, &args, &schema)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_coalesce() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+        ]);
+        let a = Int32Array::from(vec![Some(1), None, None]);
+        let b = Int32Array::from(vec![Some(10), Some(20), None]);
+        let columns: Vec<ArrayRef> = vec![Arc::new(a), Arc::new(b)];
+
+        // coalesce(a, b, 0)
+        let expr = create_physical_expr(
+            &BuiltinScalarFunction::Coalesce,
+            &vec![col("a"), col("b"), lit(ScalarValue::Int32(Some(0)))],
+            &schema,
+        )?;
+
+        // type is correct
+        assert_eq!(expr.data_type(&schema)?, DataType::Int32);
+
+        // evaluate works
+        let result =
+            expr.evaluate(&RecordBatch::try_new(Arc::new(schema.clone()), columns)?)?;
+
+        // downcast works
+        let result = result.as_any().downcast_ref::<Int32Array>().unwrap();
+
+        // values are correct
+        assert_eq!(result.null_count(), 0);
+        assert_eq!(result.value(0), 1);
+        assert_eq!(result.value(1), 20);
+        assert_eq!(result.value(2), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_now() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let columns: Vec<ArrayRef> = vec![Arc::new(Int32Array::from(vec![1, 2]))];
+
+        let before = Utc::now().timestamp_nanos();
+        let expr = create_physical_expr(&BuiltinScalarFunction::Now, &vec![], &schema)?;
+        let after = Utc::now().timestamp_nanos();
+
+        assert_eq!(
+            expr.data_type(&schema)?,
+            DataType::Timestamp(TimeUnit::Nanosecond, None)
+        );
+
+        let result =
+            expr.evaluate(&RecordBatch::try_new(Arc::new(schema.clone()), columns)?)?;
+        let result = result
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+
+        // all the rows get the time at which the expression was created
+        assert_eq!(result.len(), 2);
+        assert!(before <= result.value(0) && result.value(0) <= after);
+        assert_eq!(result.value(0), result.value(1));
+
+        Ok(())
+    }
+
     #[test]
     fn test_concat_error() -> Result<()> {
         let result = return_type(&BuiltinScalarFunction::Concat, &vec![]);
@@ -15,7 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
-//! Defines the LIMIT plan
+//! Defines the LIMIT and OFFSET plans
 
 use std::any::Any;
 use std::sync::Arc;
@@ -178,6 +178,82 @@ impl ExecutionPlan for LocalLimitExec {
     }
 }
 
+/// OffsetExec discards the first rows of its input
+#[derive(Debug)]
+pub struct OffsetExec {
+    input: Arc<dyn ExecutionPlan>,
+    /// Number of rows to discard
+    offset: usize,
+}
+
+impl OffsetExec {
+    /// Create a new OffsetExec
+    pub fn new(input: Arc<dyn ExecutionPlan>, offset: usize) -> Self {
+        Self { input, offset }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for OffsetExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        Distribution::SinglePartition
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(OffsetExec::new(children[0].clone(), self.offset))),
+            _ => Err(DataFusionError::Internal(
+                "OffsetExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        // OffsetExec has a single output partition
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "OffsetExec invalid partition {}",
+                partition
+            )));
+        }
+
+        // OffsetExec requires a single input partition
+        if 1 != self.input.output_partitioning().partition_count() {
+            return Err(DataFusionError::Internal(
+                "OffsetExec requires a single input partition".to_owned(),
+            ));
+        }
+
+        let mut it = self.input.execute(0).await?;
+        Ok(Box::pin(MemoryStream::try_new(
+            collect_with_offset(&mut it, self.offset).await?,
+            self.input.schema(),
+            None,
+        )?))
+    }
+}
+
 /// Truncate a RecordBatch to maximum of n rows
 pub fn truncate_batch(batch: &RecordBatch, n: usize) -> Result<RecordBatch> {
     let limited_columns: Result<Vec<ArrayRef>> = (0..batch.num_columns())
@@ -221,6 +297,37 @@ async fn collect_with_limit(
     }
 }
 
+/// Create a vector of record batches from an iterator, without its first
+/// `offset` rows
+async fn collect_with_offset(
+    reader: &mut SendableRecordBatchStream,
+    offset: usize,
+) -> Result<Vec<RecordBatch>> {
+    let mut skipped = 0;
+    let mut results: Vec<RecordBatch> = vec![];
+    loop {
+        match reader.as_mut().next().await {
+            Some(Ok(batch)) => {
+                let skip = (offset - skipped).min(batch.num_rows());
+                skipped += skip;
+                if skip == 0 {
+                    results.push(batch);
+                } else if skip < batch.num_rows() {
+                    let length = batch.num_rows() - skip;
+                    let columns = (0..batch.num_columns())
+                        .map(|i| batch.column(i).slice(skip, length))
+                        .collect();
+                    results.push(RecordBatch::try_new(batch.schema().clone(), columns)?);
+                }
+            }
+            None => {
+                return Ok(results);
+            }
+            Some(Err(e)) => return Err(DataFusionError::from(e)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -256,4 +363,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn offset() -> Result<()> {
+        let schema = test::aggr_test_schema();
+
+        let num_partitions = 4;
+        let path =
+            test::create_partitioned_csv("aggregate_test_100.csv", num_partitions)?;
+
+        let csv =
+            CsvExec::try_new(&path, CsvReadOptions::new().schema(&schema), None, 1024)?;
+
+        let offset = OffsetExec::new(Arc::new(MergeExec::new(Arc::new(csv))), 95);
+
+        let iter = offset.execute(0).await?;
+        let batches = common::collect(iter).await?;
+
+        // the first 95 of the 100 rows are discarded
+        let row_count: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        assert_eq!(row_count, 5);
+
+        Ok(())
+    }
 }
@@ -96,3 +96,28 @@ math_unary_function!("exp", exp);
 math_unary_function!("log", ln);
 math_unary_function!("log2", log2);
 math_unary_function!("log10", log10);
+
+/// raises the first argument to the power of the second one, both f64
+pub fn power(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let args = args
+        .iter()
+        .map(|e| match e.as_any().downcast_ref::<Float64Array>() {
+            Some(array) => Ok(array),
+            _ => Err(DataFusionError::Internal(
+                "Invalid data type for power".to_string(),
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (bases, exponents) = (args[0], args[1]);
+
+    let result = (0..bases.len())
+        .map(|i| {
+            if bases.is_null(i) || exponents.is_null(i) {
+                None
+            } else {
+                Some(bases.value(i).powf(exponents.value(i)))
+            }
+        })
+        .collect::<Vec<_>>();
+    Ok(Arc::new(Float64Array::from(result)))
+}
@@ -201,6 +201,7 @@ pub trait Accumulator: Send + Sync + Debug {
 pub mod aggregates;
 pub mod array_expressions;
 pub mod common;
+pub mod conditional_expressions;
 pub mod csv;
 pub mod datetime_expressions;
 pub mod distinct_expressions;
@@ -218,8 +219,10 @@ pub mod merge;
 pub mod parquet;
 pub mod planner;
 pub mod projection;
+pub mod regex_expressions;
 pub mod sort;
 pub mod string_expressions;
 pub mod type_coercion;
 pub mod udaf;
 pub mod udf;
+pub mod window_functions;
@@ -17,6 +17,7 @@
 
 //! Physical query planner
 
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 use super::{aggregates, empty::EmptyExec, expressions::binary, functions, udaf};
@@ -30,7 +31,7 @@ use crate::physical_plan::explain::ExplainExec;
 use crate::physical_plan::expressions::{Column, Literal, PhysicalSortExpr};
 use crate::physical_plan::filter::FilterExec;
 use crate::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
-use crate::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
+use crate::physical_plan::limit::{GlobalLimitExec, LocalLimitExec, OffsetExec};
 use crate::physical_plan::memory::MemoryExec;
 use crate::physical_plan::merge::MergeExec;
 use crate::physical_plan::parquet::ParquetExec;
@@ -39,9 +40,12 @@ use crate::physical_plan::sort::SortExec;
 use crate::physical_plan::udf;
 use crate::physical_plan::{expressions, Distribution};
 use crate::physical_plan::{AggregateExpr, ExecutionPlan, PhysicalExpr, PhysicalPlanner};
+use crate::scalar::ScalarValue;
 use crate::variable::VarType;
-use arrow::compute::SortOptions;
-use arrow::datatypes::Schema;
+use arrow::array::StringArray;
+use arrow::compute::{can_cast_types, SortOptions};
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
 use expressions::col;
 
 /// This trait permits the `DefaultPhysicalPlanner` to create plans for
@@ -309,6 +313,20 @@ impl DefaultPhysicalPlanner {
                     ctx_state.config.concurrency,
                 )))
             }
+            LogicalPlan::Offset { input, n } => {
+                let input = self.create_physical_plan(input, ctx_state)?;
+                Ok(Arc::new(OffsetExec::new(input, *n)))
+            }
+            LogicalPlan::Window { .. } => Err(DataFusionError::NotImplemented(
+                "Window functions are not supported yet".to_string(),
+            )),
+            LogicalPlan::Join { .. } => Err(DataFusionError::NotImplemented(
+                "Joins are not supported yet".to_string(),
+            )),
+            // settings are not honored by any execution plan yet
+            LogicalPlan::Settings { input, .. } => {
+                self.create_physical_plan(input, ctx_state)
+            }
             LogicalPlan::CreateExternalTable { .. } => {
                 // There is no default plan for "CREATE EXTERNAL
                 // TABLE" -- it must be handled at a higher level (so
@@ -318,6 +336,50 @@ impl DefaultPhysicalPlanner {
                     "Unsupported logical plan: CreateExternalTable".to_string(),
                 ))
             }
+            LogicalPlan::DropTable { .. } => {
+                // Like "CREATE EXTERNAL TABLE", "DROP TABLE" must be handled at a
+                // higher level, where the table can be deregistered from the context
+                Err(DataFusionError::Internal(
+                    "Unsupported logical plan: DropTable".to_string(),
+                ))
+            }
+            LogicalPlan::Update { .. } => Err(DataFusionError::NotImplemented(
+                "UPDATE is not supported yet".to_string(),
+            )),
+            LogicalPlan::DescribeTable {
+                table_schema,
+                schema,
+                ..
+            } => {
+                let fields = table_schema.fields();
+                let names: Vec<&str> =
+                    fields.iter().map(|field| field.name().as_str()).collect();
+                let data_types: Vec<String> = fields
+                    .iter()
+                    .map(|field| format!("{:?}", field.data_type()))
+                    .collect();
+                let data_types: Vec<&str> = data_types
+                    .iter()
+                    .map(|data_type| data_type.as_str())
+                    .collect();
+                let nullable: Vec<&str> = fields
+                    .iter()
+                    .map(|field| if field.is_nullable() { "YES" } else { "NO" })
+                    .collect();
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(StringArray::from(names)),
+                        Arc::new(StringArray::from(data_types)),
+                        Arc::new(StringArray::from(nullable)),
+                    ],
+                )?;
+                Ok(Arc::new(MemoryExec::try_new(
+                    &vec![vec![batch]],
+                    schema.clone(),
+                    None,
+                )?))
+            }
             LogicalPlan::Explain {
                 verbose,
                 plan,
@@ -342,6 +404,9 @@ impl DefaultPhysicalPlanner {
                 let schema_ref = Arc::new(schema.as_ref().clone());
                 Ok(Arc::new(ExplainExec::new(schema_ref, stringified_plans)))
             }
+            LogicalPlan::Analyze { .. } => Err(DataFusionError::NotImplemented(
+                "EXPLAIN ANALYZE is not supported yet".to_string(),
+            )),
             LogicalPlan::Extension { node } => {
                 let inputs = node
                     .inputs()
@@ -388,7 +453,7 @@ impl DefaultPhysicalPlanner {
                 Ok(Arc::new(Column::new(name)))
             }
             Expr::Literal(value) => Ok(Arc::new(Literal::new(value.clone()))),
-            Expr::ScalarVariable(variable_names) => {
+            Expr::ScalarVariable(_, variable_names) => {
                 if &variable_names[0][0..2] == "@@" {
                     match ctx_state.var_provider.get(&VarType::System) {
                         Some(provider) => {
@@ -423,6 +488,28 @@ impl DefaultPhysicalPlanner {
                 input_schema,
                 data_type.clone(),
             ),
+            // the cast kernel already yields NULL for the values it cannot convert,
+            // and a type it cannot convert at all yields NULL for every value
+            Expr::TryCast { expr, data_type } => {
+                let expr = self.create_physical_expr(expr, input_schema, ctx_state)?;
+                if can_cast_types(&expr.data_type(input_schema)?, data_type) {
+                    return expressions::cast(expr, input_schema, data_type.clone());
+                }
+                // temporal types have no scalar value, so their NULL is cast from
+                // the NULL of their integer representation
+                let null = match data_type {
+                    DataType::Date32(_) | DataType::Time32(_) => ScalarValue::Int32(None),
+                    DataType::Date64(_)
+                    | DataType::Time64(_)
+                    | DataType::Timestamp(_, _) => ScalarValue::Int64(None),
+                    _ => ScalarValue::try_from(data_type)?,
+                };
+                expressions::cast(
+                    Arc::new(Literal::new(null)),
+                    input_schema,
+                    data_type.clone(),
+                )
+            }
             Expr::Not(expr) => expressions::not(
                 self.create_physical_expr(expr, input_schema, ctx_state)?,
                 input_schema,
@@ -483,8 +570,13 @@ impl DefaultPhysicalPlanner {
                 fun,
                 distinct,
                 args,
-                ..
+                filter,
             } => {
+                if filter.is_some() {
+                    return Err(DataFusionError::NotImplemented(
+                        "FILTER on aggregate functions is not supported".to_string(),
+                    ));
+                }
                 let args = args
                     .iter()
                     .map(|e| self.create_physical_expr(e, input_schema, ctx_state))
@@ -562,7 +654,8 @@ mod tests {
         physical_plan::SendableRecordBatchStream,
     };
     use crate::{prelude::ExecutionConfig, test::arrow_testdata_path};
-    use arrow::datatypes::{DataType, Field, SchemaRef};
+    use arrow::array::{BooleanArray, TimestampNanosecondArray};
+    use arrow::datatypes::{DataType, DateUnit, Field, SchemaRef, TimeUnit};
     use async_trait::async_trait;
     use fmt::Debug;
     use std::{any::Any, collections::HashMap, fmt};
@@ -622,6 +715,106 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_try_cast_unsupported_conversion() -> Result<()> {
+        let schema = Schema::new(vec![Field::new(
+            "a",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true,
+        )]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(TimestampNanosecondArray::from(vec![1, 2]))],
+        )?;
+        let planner = DefaultPhysicalPlanner::default();
+
+        // the cast kernel cannot convert timestamps to floats: CAST fails, while
+        // TRY_CAST yields NULL
+        let cast = Expr::Cast {
+            expr: Box::new(col("a")),
+            data_type: DataType::Float64,
+        };
+        assert!(planner
+            .create_physical_expr(&cast, &schema, &make_ctx_state())
+            .is_err());
+
+        let try_cast = Expr::TryCast {
+            expr: Box::new(col("a")),
+            data_type: DataType::Float64,
+        };
+        let expr = planner.create_physical_expr(&try_cast, &schema, &make_ctx_state())?;
+        assert_eq!(DataType::Float64, expr.data_type(&schema)?);
+        let result = expr.evaluate(&batch)?;
+        assert_eq!(2, result.len());
+        assert_eq!(2, result.null_count());
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_cast_unsupported_temporal_conversion() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Boolean, true)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(BooleanArray::from(vec![Some(true), None]))],
+        )?;
+        let planner = DefaultPhysicalPlanner::default();
+
+        // booleans cannot be cast to temporal types, which have no scalar NULL
+        let targets = vec![
+            DataType::Date32(DateUnit::Day),
+            DataType::Time64(TimeUnit::Nanosecond),
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+        ];
+        for data_type in targets {
+            let try_cast = Expr::TryCast {
+                expr: Box::new(col("a")),
+                data_type: data_type.clone(),
+            };
+            let expr =
+                planner.create_physical_expr(&try_cast, &schema, &make_ctx_state())?;
+            assert_eq!(data_type, expr.data_type(&schema)?);
+            let result = expr.evaluate(&batch)?;
+            assert_eq!(&data_type, result.data_type());
+            assert_eq!(2, result.len());
+            assert_eq!(2, result.null_count());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_truth_value_predicates() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Boolean, true)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(BooleanArray::from(vec![
+                Some(true),
+                Some(false),
+                None,
+            ]))],
+        )?;
+        let planner = DefaultPhysicalPlanner::default();
+
+        let cases = vec![
+            (col("a").is_true(), vec![true, false, false]),
+            (col("a").is_not_true(), vec![false, true, true]),
+            (col("a").is_false(), vec![false, true, false]),
+            (col("a").is_unknown(), vec![false, false, true]),
+        ];
+        for (expr, expected) in cases {
+            let expr = planner.create_physical_expr(&expr, &schema, &make_ctx_state())?;
+            let result = expr.evaluate(&batch)?;
+            let result = result
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .expect("failed to downcast to BooleanArray");
+            assert_eq!(0, result.null_count());
+            for (i, value) in expected.iter().enumerate() {
+                assert_eq!(*value, result.value(i));
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_with_csv_plan() -> Result<()> {
         let testdata = arrow_testdata_path();
@@ -0,0 +1,200 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Regular expression functions. The patterns are compiled when the
+//! functions are evaluated, so an invalid pattern is an execution error.
+
+use std::collections::HashMap;
+
+use crate::error::{DataFusionError, Result};
+use arrow::array::{Array, ArrayRef, ListArray, ListBuilder, StringArray, StringBuilder};
+use regex::{Regex, RegexBuilder};
+
+macro_rules! downcast_vec {
+    ($ARGS:expr, $ARRAY_TYPE:ident) => {{
+        $ARGS
+            .iter()
+            .map(|e| match e.as_any().downcast_ref::<$ARRAY_TYPE>() {
+                Some(array) => Ok(array),
+                _ => Err(DataFusionError::Internal("failed to downcast".to_string())),
+            })
+    }};
+}
+
+/// The compiled regular expressions, by pattern and flags, so that a pattern
+/// shared by many rows is compiled once
+#[derive(Default)]
+struct RegexCache {
+    regexes: HashMap<(String, String), Regex>,
+}
+
+impl RegexCache {
+    /// Returns the regular expression of `pattern`. The flag `i` makes it case
+    /// insensitive, and `g` is accepted to be interpreted by the caller.
+    fn get(&mut self, pattern: &str, flags: &str) -> Result<&Regex> {
+        let key = (pattern.to_string(), flags.to_string());
+        if !self.regexes.contains_key(&key) {
+            if let Some(flag) = flags.chars().find(|c| *c != 'i' && *c != 'g') {
+                return Err(DataFusionError::Execution(format!(
+                    "Unsupported regular expression flag '{}'",
+                    flag
+                )));
+            }
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(flags.contains('i'))
+                .build()
+                .map_err(|e| {
+                    DataFusionError::Execution(format!(
+                        "Invalid regular expression '{}': {}",
+                        pattern, e
+                    ))
+                })?;
+            self.regexes.insert(key.clone(), regex);
+        }
+        Ok(&self.regexes[&key])
+    }
+}
+
+/// returns the substrings captured by the groups of the second argument, a
+/// pattern, at its first match in the first argument, or the whole match when
+/// the pattern has no groups. The result is null when the pattern does not match.
+pub fn regexp_match(args: &[ArrayRef]) -> Result<ListArray> {
+    let args = downcast_vec!(args, StringArray).collect::<Result<Vec<&StringArray>>>()?;
+    if args.len() != 2 {
+        return Err(DataFusionError::Internal(format!(
+            "Regexp_match was called with {} arguments. It requires two.",
+            args.len()
+        )));
+    }
+    let (strings, patterns) = (args[0], args[1]);
+
+    let mut cache = RegexCache::default();
+    let mut builder = ListBuilder::new(StringBuilder::new(strings.len()));
+    for index in 0..strings.len() {
+        // if any is null, the result is null
+        if strings.is_null(index) || patterns.is_null(index) {
+            builder.append(false)?;
+            continue;
+        }
+        let regex = cache.get(patterns.value(index), "")?;
+        match regex.captures(strings.value(index)) {
+            Some(captures) => {
+                // the group 0 is the whole match
+                let groups = if captures.len() > 1 { 1 } else { 0 };
+                for group in captures.iter().skip(groups) {
+                    match group {
+                        Some(group) => builder.values().append_value(group.as_str())?,
+                        None => builder.values().append_null()?,
+                    }
+                }
+                builder.append(true)?;
+            }
+            None => builder.append(false)?,
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// replaces the first match of the second argument, a pattern, in the first
+/// argument by the third argument, in which `$1` refers to the first group. The
+/// optional fourth argument holds flags: `g` replaces all the matches, and `i`
+/// matches case insensitively.
+pub fn regexp_replace(args: &[ArrayRef]) -> Result<StringArray> {
+    let args = downcast_vec!(args, StringArray).collect::<Result<Vec<&StringArray>>>()?;
+    if args.len() != 3 && args.len() != 4 {
+        return Err(DataFusionError::Internal(format!(
+            "Regexp_replace was called with {} arguments. It requires three or four.",
+            args.len()
+        )));
+    }
+    let (strings, patterns, replacements) = (args[0], args[1], args[2]);
+    let flags = args.get(3);
+
+    let mut cache = RegexCache::default();
+    let mut builder = StringBuilder::new(strings.len());
+    for index in 0..strings.len() {
+        // if any is null, the result is null
+        if strings.is_null(index)
+            || patterns.is_null(index)
+            || replacements.is_null(index)
+            || flags.map_or(false, |flags| flags.is_null(index))
+        {
+            builder.append_null()?;
+            continue;
+        }
+        let flags = flags.map_or("", |flags| flags.value(index));
+        let regex = cache.get(patterns.value(index), flags)?;
+        let limit = if flags.contains('g') { 0 } else { 1 };
+        let replaced =
+            regex.replacen(strings.value(index), limit, replacements.value(index));
+        builder.append_value(&replaced)?;
+    }
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn utf8(values: Vec<&str>) -> ArrayRef {
+        Arc::new(StringArray::from(values))
+    }
+
+    #[test]
+    fn regexp_match_groups() -> Result<()> {
+        let result = regexp_match(&[
+            utf8(vec!["John Smith", "Jane", "Bob"]),
+            utf8(vec!["^J(\\w+) (\\w+)$", "^J", "^J"]),
+        ])?;
+
+        let values = result.value(0);
+        let values = values.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(2, values.len());
+        assert_eq!("ohn", values.value(0));
+        assert_eq!("Smith", values.value(1));
+
+        // without groups, the whole match
+        let values = result.value(1);
+        let values = values.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!("J", values.value(0));
+
+        assert!(result.is_null(2));
+        Ok(())
+    }
+
+    #[test]
+    fn regexp_replace_flags() -> Result<()> {
+        let strings = utf8(vec!["aAaA", "aAaA", "aAaA"]);
+        let patterns = utf8(vec!["a", "a", "a"]);
+        let replacements = utf8(vec!["x", "x", "x"]);
+        let flags = utf8(vec!["", "g", "gi"]);
+        let result = regexp_replace(&[strings, patterns, replacements, flags])?;
+        assert_eq!("xAaA", result.value(0));
+        assert_eq!("xAxA", result.value(1));
+        assert_eq!("xxxx", result.value(2));
+        Ok(())
+    }
+
+    #[test]
+    fn regexp_invalid_pattern() {
+        let err = regexp_replace(&[utf8(vec!["a"]), utf8(vec!["("]), utf8(vec!["x"])])
+            .expect_err("the pattern is invalid");
+        assert!(format!("{:?}", err)
+            .starts_with("Execution(\"Invalid regular expression '('"));
+    }
+}
@@ -18,7 +18,7 @@
 //! String expressions
 
 use crate::error::{DataFusionError, Result};
-use arrow::array::{Array, ArrayRef, StringArray, StringBuilder};
+use arrow::array::{Array, ArrayRef, Int32Array, Int64Array, StringArray, StringBuilder};
 
 macro_rules! downcast_vec {
     ($ARGS:expr, $ARRAY_TYPE:ident) => {{
@@ -66,3 +66,137 @@ pub fn concatenate(args: &[ArrayRef]) -> Result<StringArray> {
     }
     Ok(builder.finish())
 }
+
+/// Which ends of a string a trim function removes characters from
+#[derive(Clone, Copy)]
+enum TrimWhere {
+    Both,
+    Leading,
+    Trailing,
+}
+
+/// removes the characters of the optional second argument (a space by
+/// default) from one or both ends of each string of the first argument.
+fn trim_impl(args: &[ArrayRef], trim_where: TrimWhere) -> Result<StringArray> {
+    let args = downcast_vec!(args, StringArray).collect::<Result<Vec<&StringArray>>>()?;
+    if args.is_empty() || args.len() > 2 {
+        return Err(DataFusionError::Internal(format!(
+            "Trim was called with {} arguments. It requires one or two.",
+            args.len()
+        )));
+    }
+    let strings = args[0];
+    let characters = args.get(1);
+
+    let mut builder = StringBuilder::new(strings.len());
+    for index in 0..strings.len() {
+        let chars = match characters {
+            Some(characters) if characters.is_null(index) => None,
+            Some(characters) => Some(characters.value(index)),
+            None => Some(" "),
+        };
+        match chars {
+            Some(chars) if !strings.is_null(index) => {
+                let value = strings.value(index);
+                let pattern = |c: char| chars.contains(c);
+                let trimmed = match trim_where {
+                    TrimWhere::Both => value.trim_matches(pattern),
+                    TrimWhere::Leading => value.trim_start_matches(pattern),
+                    TrimWhere::Trailing => value.trim_end_matches(pattern),
+                };
+                builder.append_value(trimmed)?;
+            }
+            // if any is null, the result is null
+            _ => builder.append_null()?,
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// removes characters (spaces by default) from both ends of a string column.
+pub fn trim(args: &[ArrayRef]) -> Result<StringArray> {
+    trim_impl(args, TrimWhere::Both)
+}
+
+/// removes characters (spaces by default) from the start of a string column.
+pub fn ltrim(args: &[ArrayRef]) -> Result<StringArray> {
+    trim_impl(args, TrimWhere::Leading)
+}
+
+/// removes characters (spaces by default) from the end of a string column.
+pub fn rtrim(args: &[ArrayRef]) -> Result<StringArray> {
+    trim_impl(args, TrimWhere::Trailing)
+}
+
+/// returns the position, starting at 1, of the first occurrence of the second
+/// argument in the first one, in characters, or 0 when it does not occur.
+pub fn strpos(args: &[ArrayRef]) -> Result<Int32Array> {
+    let args = downcast_vec!(args, StringArray).collect::<Result<Vec<&StringArray>>>()?;
+    if args.len() != 2 {
+        return Err(DataFusionError::Internal(format!(
+            "Strpos was called with {} arguments. It requires two.",
+            args.len()
+        )));
+    }
+    let (strings, substrings) = (args[0], args[1]);
+
+    let positions = (0..strings.len())
+        .map(|index| {
+            // if any is null, the result is null
+            if strings.is_null(index) || substrings.is_null(index) {
+                return None;
+            }
+            let string = strings.value(index);
+            Some(match string.find(substrings.value(index)) {
+                Some(offset) => string[..offset].chars().count() as i32 + 1,
+                None => 0,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(Int32Array::from(positions))
+}
+
+/// replaces the characters of the first argument starting at the position of
+/// the third argument (starting at 1) by the second argument. The number of
+/// characters replaced is the optional fourth argument, by default the length
+/// of the replacement.
+pub fn overlay(args: &[ArrayRef]) -> Result<StringArray> {
+    if args.len() != 3 && args.len() != 4 {
+        return Err(DataFusionError::Internal(format!(
+            "Overlay was called with {} arguments. It requires three or four.",
+            args.len()
+        )));
+    }
+    let strings =
+        downcast_vec!(args[..2], StringArray).collect::<Result<Vec<&StringArray>>>()?;
+    let integers =
+        downcast_vec!(args[2..], Int64Array).collect::<Result<Vec<&Int64Array>>>()?;
+    let (strings, replacements) = (strings[0], strings[1]);
+    let (positions, counts) = (integers[0], integers.get(1));
+
+    let mut builder = StringBuilder::new(strings.len());
+    for index in 0..strings.len() {
+        // if any is null, the result is null
+        if strings.is_null(index)
+            || replacements.is_null(index)
+            || positions.is_null(index)
+            || counts.map_or(false, |counts| counts.is_null(index))
+        {
+            builder.append_null()?;
+            continue;
+        }
+        let string = strings.value(index);
+        let replacement = replacements.value(index);
+        let count = match counts {
+            Some(counts) => counts.value(index),
+            None => replacement.chars().count() as i64,
+        };
+        let start = positions.value(index) - 1;
+
+        let mut value: String = string.chars().take(start.max(0) as usize).collect();
+        value.push_str(replacement);
+        value.extend(string.chars().skip((start + count).max(0) as usize));
+        builder.append_value(&value)?;
+    }
+    Ok(builder.finish())
+}
@@ -95,6 +95,11 @@ pub fn data_types(
             }
             vec![(0..*number).map(|i| current_types[i].clone()).collect()]
         }
+        // the types of each signature the arguments can be coerced to
+        Signature::OneOf(signatures) => signatures
+            .iter()
+            .filter_map(|signature| data_types(current_types, signature).ok())
+            .collect(),
     };
 
     if valid_types.contains(current_types) {
@@ -313,6 +318,15 @@ mod tests {
                 Signature::Any(1),
                 vec![DataType::Float32],
             )?,
+            // the signature with a matching number of arguments, i32 -> i64
+            case(
+                vec![DataType::Utf8, DataType::Int32],
+                Signature::OneOf(vec![
+                    Signature::Exact(vec![DataType::Utf8]),
+                    Signature::Exact(vec![DataType::Utf8, DataType::Int64]),
+                ]),
+                vec![DataType::Utf8, DataType::Int64],
+            )?,
         ];
 
         for case in cases {
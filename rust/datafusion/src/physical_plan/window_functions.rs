@@ -0,0 +1,198 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Declaration of built-in window functions.
+//! This module contains the enumeration of the functions that can be evaluated
+//! over a window (`OVER (...)`) and their metadata.
+//!
+//! A window function is either a built-in window function, such as ROW_NUMBER,
+//! or a built-in aggregate function, such as SUM, evaluated over a window.
+
+use super::aggregates;
+use crate::error::{DataFusionError, Result};
+use arrow::datatypes::DataType;
+use std::{fmt, str::FromStr};
+
+/// Enum of all functions that can be evaluated over a window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowFunction {
+    /// a built-in aggregate function evaluated over a window
+    AggregateFunction(aggregates::AggregateFunction),
+    /// a built-in window function
+    BuiltInWindowFunction(BuiltInWindowFunction),
+}
+
+/// Enum of all built-in window functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuiltInWindowFunction {
+    /// number of the current row within its partition, counting from 1
+    RowNumber,
+    /// rank of the current row with gaps
+    Rank,
+    /// value of its argument at the first row of the window frame
+    FirstValue,
+    /// value of its argument at the last row of the window frame
+    LastValue,
+    /// value of its argument at the row that is a given offset before the current
+    /// row, or a default value
+    Lag,
+    /// value of its argument at the row that is a given offset after the current
+    /// row, or a default value
+    Lead,
+}
+
+impl fmt::Display for WindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindowFunction::AggregateFunction(fun) => write!(f, "{}", fun),
+            WindowFunction::BuiltInWindowFunction(fun) => write!(f, "{}", fun),
+        }
+    }
+}
+
+impl fmt::Display for BuiltInWindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuiltInWindowFunction::RowNumber => write!(f, "ROW_NUMBER"),
+            BuiltInWindowFunction::Rank => write!(f, "RANK"),
+            BuiltInWindowFunction::FirstValue => write!(f, "FIRST_VALUE"),
+            BuiltInWindowFunction::LastValue => write!(f, "LAST_VALUE"),
+            BuiltInWindowFunction::Lag => write!(f, "LAG"),
+            BuiltInWindowFunction::Lead => write!(f, "LEAD"),
+        }
+    }
+}
+
+impl FromStr for WindowFunction {
+    type Err = DataFusionError;
+    fn from_str(name: &str) -> Result<WindowFunction> {
+        if let Ok(fun) = BuiltInWindowFunction::from_str(name) {
+            return Ok(WindowFunction::BuiltInWindowFunction(fun));
+        }
+        if let Ok(fun) = aggregates::AggregateFunction::from_str(name) {
+            return Ok(WindowFunction::AggregateFunction(fun));
+        }
+        Err(DataFusionError::Plan(format!(
+            "There is no built-in window function named {}",
+            name
+        )))
+    }
+}
+
+impl FromStr for BuiltInWindowFunction {
+    type Err = DataFusionError;
+    fn from_str(name: &str) -> Result<BuiltInWindowFunction> {
+        Ok(match &*name.to_uppercase() {
+            "ROW_NUMBER" => BuiltInWindowFunction::RowNumber,
+            "RANK" => BuiltInWindowFunction::Rank,
+            "FIRST_VALUE" => BuiltInWindowFunction::FirstValue,
+            "LAST_VALUE" => BuiltInWindowFunction::LastValue,
+            "LAG" => BuiltInWindowFunction::Lag,
+            "LEAD" => BuiltInWindowFunction::Lead,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "There is no built-in window function named {}",
+                    name
+                )))
+            }
+        })
+    }
+}
+
+/// Returns the datatype of the window function
+pub fn return_type(fun: &WindowFunction, arg_types: &Vec<DataType>) -> Result<DataType> {
+    match fun {
+        WindowFunction::AggregateFunction(fun) => aggregates::return_type(fun, arg_types),
+        WindowFunction::BuiltInWindowFunction(fun) => match fun {
+            BuiltInWindowFunction::RowNumber | BuiltInWindowFunction::Rank => {
+                if !arg_types.is_empty() {
+                    return Err(DataFusionError::Plan(format!(
+                        "The function {} expects no arguments, got {}",
+                        fun,
+                        arg_types.len()
+                    )));
+                }
+                Ok(DataType::UInt64)
+            }
+            BuiltInWindowFunction::FirstValue | BuiltInWindowFunction::LastValue => {
+                if arg_types.len() != 1 {
+                    return Err(DataFusionError::Plan(format!(
+                        "The function {} expects 1 argument, got {}",
+                        fun,
+                        arg_types.len()
+                    )));
+                }
+                Ok(arg_types[0].clone())
+            }
+            // LAG(expr [, offset [, default]])
+            BuiltInWindowFunction::Lag | BuiltInWindowFunction::Lead => {
+                if arg_types.is_empty() || arg_types.len() > 3 {
+                    return Err(DataFusionError::Plan(format!(
+                        "The function {} expects 1 to 3 arguments, got {}",
+                        fun,
+                        arg_types.len()
+                    )));
+                }
+                Ok(arg_types[0].clone())
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_function_from_str() -> Result<()> {
+        assert_eq!(
+            WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::RowNumber),
+            WindowFunction::from_str("row_number")?
+        );
+        assert_eq!(
+            WindowFunction::AggregateFunction(aggregates::AggregateFunction::Sum),
+            WindowFunction::from_str("SUM")?
+        );
+        assert!(WindowFunction::from_str("sqrt").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn window_function_return_type() -> Result<()> {
+        let fun = WindowFunction::from_str("rank")?;
+        assert_eq!(DataType::UInt64, return_type(&fun, &vec![])?);
+        assert!(return_type(&fun, &vec![DataType::Int32]).is_err());
+
+        let fun = WindowFunction::from_str("first_value")?;
+        assert_eq!(DataType::Utf8, return_type(&fun, &vec![DataType::Utf8])?);
+        assert!(return_type(&fun, &vec![]).is_err());
+
+        let fun = WindowFunction::from_str("lag")?;
+        assert_eq!(
+            DataType::Int32,
+            return_type(
+                &fun,
+                &vec![DataType::Int32, DataType::Int64, DataType::Int64]
+            )?
+        );
+        assert!(return_type(&fun, &vec![DataType::Int32; 4]).is_err());
+
+        let fun = WindowFunction::from_str("max")?;
+        assert_eq!(DataType::Int32, return_type(&fun, &vec![DataType::Int32])?);
+        Ok(())
+    }
+}
@@ -20,9 +20,9 @@
 use std::{convert::TryFrom, fmt, sync::Arc};
 
 use arrow::array::{
-    Array, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array,
-    Int8Array, LargeStringArray, ListArray, StringArray, UInt16Array, UInt32Array,
-    UInt64Array, UInt8Array,
+    Array, BinaryArray, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array,
+    Int64Array, Int8Array, LargeStringArray, ListArray, StringArray, UInt16Array,
+    UInt32Array, UInt64Array, UInt8Array,
 };
 use arrow::array::{
     Int16Builder, Int32Builder, Int64Builder, Int8Builder, ListBuilder, UInt16Builder,
@@ -62,6 +62,8 @@ pub enum ScalarValue {
     Utf8(Option<String>),
     /// utf-8 encoded string representing a LargeString's arrow type.
     LargeUtf8(Option<String>),
+    /// binary
+    Binary(Option<Vec<u8>>),
     /// list of nested ScalarValue
     List(Option<Vec<ScalarValue>>, DataType),
 }
@@ -123,6 +125,7 @@ impl ScalarValue {
             ScalarValue::Float64(_) => DataType::Float64,
             ScalarValue::Utf8(_) => DataType::Utf8,
             ScalarValue::LargeUtf8(_) => DataType::LargeUtf8,
+            ScalarValue::Binary(_) => DataType::Binary,
             ScalarValue::List(_, data_type) => {
                 DataType::List(Box::new(data_type.clone()))
             }
@@ -145,6 +148,7 @@ impl ScalarValue {
             | ScalarValue::Float64(None)
             | ScalarValue::Utf8(None)
             | ScalarValue::LargeUtf8(None)
+            | ScalarValue::Binary(None)
             | ScalarValue::List(None, _) => true,
             _ => false,
         }
@@ -168,6 +172,9 @@ impl ScalarValue {
             ScalarValue::LargeUtf8(e) => {
                 Arc::new(LargeStringArray::from(vec![e.as_deref()]))
             }
+            ScalarValue::Binary(e) => {
+                Arc::new(BinaryArray::from_opt_vec(vec![e.as_deref()]))
+            }
             ScalarValue::List(values, data_type) => Arc::new(match data_type {
                 DataType::Int8 => build_list!(Int8Builder, Int8, values),
                 DataType::Int16 => build_list!(Int16Builder, Int16, values),
@@ -198,6 +205,7 @@ impl ScalarValue {
             DataType::Int8 => typed_cast!(array, index, Int8Array, Int8),
             DataType::Utf8 => typed_cast!(array, index, StringArray, Utf8),
             DataType::LargeUtf8 => typed_cast!(array, index, LargeStringArray, LargeUtf8),
+            DataType::Binary => typed_cast!(array, index, BinaryArray, Binary),
             DataType::List(nested_type) => {
                 let list_array = array.as_any().downcast_ref::<ListArray>().ok_or(
                     DataFusionError::Internal("Failed to downcast ListArray".to_string()),
@@ -308,6 +316,7 @@ impl TryFrom<&DataType> for ScalarValue {
             &DataType::UInt64 => ScalarValue::UInt64(None),
             &DataType::Utf8 => ScalarValue::Utf8(None),
             &DataType::LargeUtf8 => ScalarValue::LargeUtf8(None),
+            &DataType::Binary => ScalarValue::Binary(None),
             &DataType::List(ref nested_type) => {
                 ScalarValue::List(None, *nested_type.clone())
             }
@@ -346,6 +355,14 @@ impl fmt::Display for ScalarValue {
             ScalarValue::UInt64(e) => format_option!(f, e)?,
             ScalarValue::Utf8(e) => format_option!(f, e)?,
             ScalarValue::LargeUtf8(e) => format_option!(f, e)?,
+            ScalarValue::Binary(e) => match e {
+                Some(bytes) => {
+                    for byte in bytes {
+                        write!(f, "{:02x}", byte)?;
+                    }
+                }
+                None => write!(f, "NULL")?,
+            },
             ScalarValue::List(e, _) => match e {
                 Some(l) => write!(
                     f,
@@ -378,6 +395,7 @@ impl fmt::Debug for ScalarValue {
             ScalarValue::UInt64(_) => write!(f, "UInt64({})", self),
             ScalarValue::Utf8(_) => write!(f, "Utf8(\"{}\")", self),
             ScalarValue::LargeUtf8(_) => write!(f, "LargeUtf8(\"{}\")", self),
+            ScalarValue::Binary(_) => write!(f, "Binary(\"{}\")", self),
             ScalarValue::List(_, _) => write!(f, "List([{}])", self),
         }
     }
@@ -399,6 +417,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn scalar_binary_to_array() -> Result<()> {
+        let array_ref = ScalarValue::Binary(Some(vec![0x1f, 0xa0])).to_array();
+        let array = array_ref.as_any().downcast_ref::<BinaryArray>().unwrap();
+        assert_eq!(array.len(), 1);
+        assert_eq!(array.value(0), &[0x1f, 0xa0]);
+
+        assert_eq!(
+            ScalarValue::Binary(Some(vec![0x1f, 0xa0])),
+            ScalarValue::try_from_array(&array_ref, 0)?
+        );
+        assert_eq!(
+            "Binary(\"1fa0\")",
+            format!("{:?}", ScalarValue::Binary(Some(vec![0x1f, 0xa0])))
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn scalar_list_to_array() -> Result<()> {
         let list_array_ref = ScalarValue::List(
@@ -20,7 +20,7 @@
 //! Declares a SQL parser based on sqlparser that handles custom formats that we need.
 
 use sqlparser::{
-    ast::{ColumnDef, Statement as SQLStatement, TableConstraint},
+    ast::{ColumnDef, Statement as SQLStatement, TableConstraint, Value},
     dialect::{keywords::Keyword, Dialect, GenericDialect},
     parser::{Parser, ParserError},
     tokenizer::{Token, Tokenizer},
@@ -42,6 +42,8 @@ pub enum FileType {
     Parquet,
     /// Comma separated values
     CSV,
+    /// Apache Avro row-oriented storage
+    Avro,
 }
 
 /// DataFusion extension DDL for `CREATE EXTERNAL TABLE`
@@ -51,23 +53,50 @@ pub struct CreateExternalTable {
     pub name: String,
     /// Optional schema
     pub columns: Vec<ColumnDef>,
-    /// File type (Parquet, NDJSON, CSV)
+    /// File type (Parquet, NDJSON, CSV, Avro)
     pub file_type: FileType,
     /// CSV Header row?
     pub has_header: bool,
+    /// CSV field delimiter, as written in the `WITH (DELIMITER '...')` option
+    pub delimiter: Option<String>,
+    /// Compression codec of the files, as written in the `WITH (COMPRESSION '...')`
+    /// option
+    pub compression: Option<String>,
+    /// Partition columns, from `PARTITIONED BY (...)`
+    pub partition_columns: Vec<String>,
     /// Path to file
     pub location: String,
+    /// Whether the statement is a no-op if the table already exists
+    pub if_not_exists: bool,
 }
 
-/// DataFusion extension DDL for `EXPLAIN` and `EXPLAIN VERBOSE`
+/// DataFusion extension DDL for `EXPLAIN [ANALYZE] [VERBOSE]`
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExplainPlan {
+    /// If true, the statement is executed and its runtime metrics are reported
+    pub analyze: bool,
     /// If true, dumps more intermediate plans and results of optimizaton passes
     pub verbose: bool,
     /// The statement for which to generate an planning explanation
     pub statement: Box<Statement>,
 }
 
+/// DataFusion extension for `DESCRIBE <table>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescribeTable {
+    /// Table name
+    pub name: String,
+}
+
+/// DataFusion extension for a query followed by `SETTINGS key = value [, ...]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuerySettings {
+    /// The statement the settings apply to
+    pub statement: Box<Statement>,
+    /// The settings, in the order they were given
+    pub settings: Vec<(String, Value)>,
+}
+
 /// DataFusion Statement representations.
 ///
 /// Tokens parsed by `DFParser` are converted into these values.
@@ -79,6 +108,10 @@ pub enum Statement {
     CreateExternalTable(CreateExternalTable),
     /// Extension: `EXPLAIN <SQL>`
     Explain(ExplainPlan),
+    /// Extension: `<query> SETTINGS key = value [, ...]`
+    Settings(QuerySettings),
+    /// Extension: `DESCRIBE <table>`
+    DescribeTable(DescribeTable),
 }
 
 /// SQL Parser
@@ -159,19 +192,61 @@ impl DFParser {
                         self.parser.next_token();
                         self.parse_explain()
                     }
+                    _ if w.value.to_uppercase() == "DESCRIBE" => {
+                        self.parser.next_token();
+                        self.parse_describe()
+                    }
                     _ => {
                         // use the native parser
-                        Ok(Statement::Statement(self.parser.parse_statement()?))
+                        let statement = self.parser.parse_statement()?;
+                        self.parse_settings(statement)
                     }
                 }
             }
             _ => {
                 // use the native parser
-                Ok(Statement::Statement(self.parser.parse_statement()?))
+                let statement = self.parser.parse_statement()?;
+                self.parse_settings(statement)
             }
         }
     }
 
+    /// Parse the optional `SETTINGS key = value [, ...]` clause following a query.
+    ///
+    /// Note that a `SETTINGS` directly following a table or column name is parsed
+    /// by sqlparser as its alias.
+    fn parse_settings(
+        &mut self,
+        statement: SQLStatement,
+    ) -> Result<Statement, ParserError> {
+        let has_settings = match (&statement, self.parser.peek_token()) {
+            (SQLStatement::Query(_), Token::Word(w)) => match w.keyword {
+                Keyword::NoKeyword => w.value.to_uppercase() == "SETTINGS",
+                _ => false,
+            },
+            _ => false,
+        };
+        let statement = Statement::Statement(statement);
+        if !has_settings {
+            return Ok(statement);
+        }
+        self.parser.next_token();
+
+        let mut settings = vec![];
+        loop {
+            let name = self.parser.parse_identifier()?;
+            self.parser.expect_token(&Token::Eq)?;
+            settings.push((name.value, self.parser.parse_value()?));
+            if !self.parser.consume_token(&Token::Comma) {
+                break;
+            }
+        }
+        Ok(Statement::Settings(QuerySettings {
+            statement: Box::new(statement),
+            settings,
+        }))
+    }
+
     /// Parse a SQL CREATE statement
     pub fn parse_create(&mut self) -> Result<Statement, ParserError> {
         if self.parser.parse_keyword(Keyword::EXTERNAL) {
@@ -184,23 +259,27 @@ impl DFParser {
     /// Parse an SQL EXPLAIN statement.
     pub fn parse_explain(&mut self) -> Result<Statement, ParserError> {
         // Parser is at the token immediately after EXPLAIN
-        // Check for EXPLAIN VERBOSE
-        let verbose = match self.parser.peek_token() {
-            Token::Word(w) => match w.keyword {
-                Keyword::NoKeyword if w.value.to_uppercase() == "VERBOSE" => {
-                    self.parser.next_token();
-                    true
-                }
-                _ => false,
-            },
-            _ => false,
-        };
+        // Check for EXPLAIN ANALYZE and EXPLAIN VERBOSE
+        let analyze = self.consume_word("ANALYZE");
+        let verbose = self.consume_word("VERBOSE");
 
         let statement = Box::new(self.parse_statement()?);
-        let explain_plan = ExplainPlan { statement, verbose };
+        let explain_plan = ExplainPlan {
+            analyze,
+            verbose,
+            statement,
+        };
         Ok(Statement::Explain(explain_plan))
     }
 
+    /// Parse a DESCRIBE statement, starting at the token after DESCRIBE
+    pub fn parse_describe(&mut self) -> Result<Statement, ParserError> {
+        let name = self.parser.parse_object_name()?;
+        Ok(Statement::DescribeTable(DescribeTable {
+            name: name.to_string(),
+        }))
+    }
+
     // This is a copy of the equivalent implementation in sqlparser.
     fn parse_columns(
         &mut self,
@@ -265,6 +344,9 @@ impl DFParser {
 
     fn parse_create_external_table(&mut self) -> Result<Statement, ParserError> {
         self.parser.expect_keyword(Keyword::TABLE)?;
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
         let table_name = self.parser.parse_object_name()?;
         let (columns, _) = self.parse_columns()?;
         self.parser
@@ -273,7 +355,9 @@ impl DFParser {
         // THIS is the main difference: we parse a different file format.
         let file_type = self.parse_file_format()?;
 
-        let has_header = self.parse_csv_has_header();
+        let (has_header, delimiter, compression) = self.parse_external_table_options()?;
+
+        let partition_columns = self.parse_partition_columns()?;
 
         self.parser.expect_keyword(Keyword::LOCATION)?;
         let location = self.parser.parse_literal_string()?;
@@ -283,7 +367,11 @@ impl DFParser {
             columns,
             file_type,
             has_header,
+            delimiter,
+            compression,
+            partition_columns,
             location,
+            if_not_exists,
         };
         Ok(Statement::CreateExternalTable(create))
     }
@@ -293,11 +381,28 @@ impl DFParser {
         match self.parser.next_token() {
             Token::Word(w) => match &*w.value {
                 "PARQUET" => Ok(FileType::Parquet),
-                "NDJSON" => Ok(FileType::NdJson),
+                "NDJSON" | "JSON" => Ok(FileType::NdJson),
                 "CSV" => Ok(FileType::CSV),
-                _ => self.expected("one of PARQUET, NDJSON, or CSV", Token::Word(w)),
+                "AVRO" => Ok(FileType::Avro),
+                _ => self.expected(
+                    "one of PARQUET, NDJSON, JSON, CSV, or AVRO",
+                    Token::Word(w),
+                ),
             },
-            unexpected => self.expected("one of PARQUET, NDJSON, or CSV", unexpected),
+            unexpected => {
+                self.expected("one of PARQUET, NDJSON, JSON, CSV, or AVRO", unexpected)
+            }
+        }
+    }
+
+    /// Consumes the next token if it is the word `expected`, in any case
+    fn consume_word(&mut self, expected: &str) -> bool {
+        match self.parser.peek_token() {
+            Token::Word(w) if w.value.to_uppercase() == expected => {
+                self.parser.next_token();
+                true
+            }
+            _ => false,
         }
     }
 
@@ -310,10 +415,68 @@ impl DFParser {
         }
     }
 
-    fn parse_csv_has_header(&mut self) -> bool {
-        self.consume_token("WITH")
-            & self.consume_token("HEADER")
-            & self.consume_token("ROW")
+    /// Parses the optional `PARTITIONED BY (col, ...)` clause
+    fn parse_partition_columns(&mut self) -> Result<Vec<String>, ParserError> {
+        let partitioned = match self.parser.peek_token() {
+            Token::Word(w) => w.value.to_uppercase() == "PARTITIONED",
+            _ => false,
+        };
+        if !partitioned {
+            return Ok(vec![]);
+        }
+        self.parser.next_token();
+        self.parser.expect_keyword(Keyword::BY)?;
+        self.parser.expect_token(&Token::LParen)?;
+        let mut columns = vec![];
+        loop {
+            columns.push(self.parser.parse_identifier()?.value);
+            if !self.parser.consume_token(&Token::Comma) {
+                break;
+            }
+        }
+        self.parser.expect_token(&Token::RParen)?;
+        Ok(columns)
+    }
+
+    /// Parses the `WITH HEADER ROW` and `WITH (DELIMITER '...', COMPRESSION '...')`
+    /// options, returning whether the file has a header row, and the delimiter and
+    /// compression codec, if any
+    fn parse_external_table_options(
+        &mut self,
+    ) -> Result<(bool, Option<String>, Option<String>), ParserError> {
+        let mut has_header = false;
+        let mut delimiter = None;
+        let mut compression = None;
+        while self.consume_token("WITH") {
+            if self.parser.consume_token(&Token::LParen) {
+                loop {
+                    let option = self.parser.parse_identifier()?;
+                    match &*option.value.to_uppercase() {
+                        "DELIMITER" => {
+                            delimiter = Some(self.parser.parse_literal_string()?)
+                        }
+                        "COMPRESSION" => {
+                            compression = Some(self.parser.parse_literal_string()?)
+                        }
+                        _ => {
+                            return parser_err!(format!(
+                                "Unsupported table option {}",
+                                option
+                            ))
+                        }
+                    }
+                    if !self.parser.consume_token(&Token::Comma) {
+                        break;
+                    }
+                }
+                self.parser.expect_token(&Token::RParen)?;
+            } else if self.consume_token("HEADER") && self.consume_token("ROW") {
+                has_header = true;
+            } else {
+                return self.expected("HEADER ROW or (", self.parser.peek_token());
+            }
+        }
+        Ok((has_header, delimiter, compression))
     }
 }
 
@@ -377,7 +540,11 @@ mod tests {
             columns: vec![make_column_def("c1", DataType::Int)],
             file_type: FileType::CSV,
             has_header: false,
+            delimiter: None,
+            compression: None,
+            partition_columns: vec![],
             location: "foo.csv".into(),
+            if_not_exists: false,
         });
         expect_parse_ok(sql, expected)?;
 
@@ -388,18 +555,183 @@ mod tests {
             columns: vec![],
             file_type: FileType::Parquet,
             has_header: false,
+            delimiter: None,
+            compression: None,
+            partition_columns: vec![],
+            location: "foo.parquet".into(),
+            if_not_exists: false,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: avro files
+        let sql = "CREATE EXTERNAL TABLE t STORED AS AVRO LOCATION 'foo.avro'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![],
+            file_type: FileType::Avro,
+            has_header: false,
+            delimiter: None,
+            compression: None,
+            partition_columns: vec![],
+            location: "foo.avro".into(),
+            if_not_exists: false,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: JSON is an alias of NDJSON
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS JSON LOCATION 'foo.json'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![make_column_def("c1", DataType::Int)],
+            file_type: FileType::NdJson,
+            has_header: false,
+            delimiter: None,
+            compression: None,
+            partition_columns: vec![],
+            location: "foo.json".into(),
+            if_not_exists: false,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: header row and delimiter
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV WITH HEADER ROW \
+                   WITH (DELIMITER '|') LOCATION 'foo.csv'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![make_column_def("c1", DataType::Int)],
+            file_type: FileType::CSV,
+            has_header: true,
+            delimiter: Some("|".into()),
+            compression: None,
+            partition_columns: vec![],
+            location: "foo.csv".into(),
+            if_not_exists: false,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: compression
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV \
+                   WITH (DELIMITER '|', COMPRESSION 'gzip') LOCATION 'foo.csv.gz'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![make_column_def("c1", DataType::Int)],
+            file_type: FileType::CSV,
+            has_header: false,
+            delimiter: Some("|".into()),
+            compression: Some("gzip".into()),
+            partition_columns: vec![],
+            location: "foo.csv.gz".into(),
+            if_not_exists: false,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: partitioned table
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV \
+                   PARTITIONED BY (year, month) LOCATION 'foo'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![make_column_def("c1", DataType::Int)],
+            file_type: FileType::CSV,
+            has_header: false,
+            delimiter: None,
+            compression: None,
+            partition_columns: vec!["year".into(), "month".into()],
+            location: "foo".into(),
+            if_not_exists: false,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: if not exists
+        let sql = "CREATE EXTERNAL TABLE IF NOT EXISTS t STORED AS PARQUET \
+                   LOCATION 'foo.parquet'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![],
+            file_type: FileType::Parquet,
+            has_header: false,
+            delimiter: None,
+            compression: None,
+            partition_columns: vec![],
             location: "foo.parquet".into(),
+            if_not_exists: true,
         });
         expect_parse_ok(sql, expected)?;
 
+        // Error cases: unknown table option
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV WITH (QUOTE '\"') \
+                   LOCATION 'foo.csv'";
+        expect_parse_error(sql, "Unsupported table option QUOTE")?;
+
         // Error cases: Invalid type
         let sql =
             "CREATE EXTERNAL TABLE t(c1 int) STORED AS UNKNOWN_TYPE LOCATION 'foo.csv'";
         expect_parse_error(
             sql,
-            "Expected one of PARQUET, NDJSON, or CSV, found: UNKNOWN_TYPE",
+            "Expected one of PARQUET, NDJSON, JSON, CSV, or AVRO, found: UNKNOWN_TYPE",
         )?;
 
         Ok(())
     }
+
+    #[test]
+    fn explain_analyze() -> Result<(), ParserError> {
+        let cases = vec![
+            ("EXPLAIN SELECT 1", false, false),
+            ("EXPLAIN VERBOSE SELECT 1", false, true),
+            ("explain analyze SELECT 1", true, false),
+            ("EXPLAIN ANALYZE VERBOSE SELECT 1", true, true),
+        ];
+        for (sql, analyze, verbose) in cases {
+            match &DFParser::parse_sql(sql)?[0] {
+                Statement::Explain(explain) => {
+                    assert_eq!(analyze, explain.analyze);
+                    assert_eq!(verbose, explain.verbose);
+                }
+                statement => panic!("unexpected statement {:?}", statement),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn describe_table() -> Result<(), ParserError> {
+        let sql = "DESCRIBE person";
+        let expected = Statement::DescribeTable(DescribeTable {
+            name: "person".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql = "describe public.person";
+        let expected = Statement::DescribeTable(DescribeTable {
+            name: "public.person".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_settings() -> Result<(), ParserError> {
+        let sql = "SELECT * FROM t LIMIT 10 SETTINGS max_rows = 5, timeout = '30'";
+        let statements = DFParser::parse_sql(sql)?;
+        match &statements[0] {
+            Statement::Settings(s) => assert_eq!(
+                vec![
+                    ("max_rows".to_string(), Value::Number("5".to_string())),
+                    (
+                        "timeout".to_string(),
+                        Value::SingleQuotedString("30".to_string())
+                    ),
+                ],
+                s.settings
+            ),
+            other => panic!("Expected query settings, found: {:?}", other),
+        }
+
+        // Error cases: missing value
+        let sql = "SELECT * FROM t LIMIT 10 SETTINGS max_rows =";
+        assert!(DFParser::parse_sql(sql).is_err());
+
+        Ok(())
+    }
 }
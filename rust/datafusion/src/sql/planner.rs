@@ -17,12 +17,15 @@
 
 //! SQL Query Planner (produces logical plan from SQL AST)
 
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::logical_plan::Expr::Alias;
 use crate::logical_plan::{
-    lit, Expr, LogicalPlan, LogicalPlanBuilder, Operator, PlanType, StringifiedPlan,
+    col, grouping_set_to_exprlist, lit, Expr, JoinType, LogicalPlan, LogicalPlanBuilder,
+    Operator, PlanType, StringifiedPlan,
 };
 use crate::scalar::ScalarValue;
 use crate::{
@@ -30,20 +33,30 @@ use crate::{
     physical_plan::udaf::AggregateUDF,
 };
 use crate::{
+    optimizer::utils,
+    physical_plan::datetime_expressions::string_to_timestamp_nanos,
     physical_plan::udf::ScalarUDF,
-    physical_plan::{aggregates, functions},
-    sql::parser::{CreateExternalTable, FileType, Statement as DFStatement},
+    physical_plan::{
+        aggregates, expressions::numerical_coercion, functions, window_functions,
+    },
+    sql::parser::{
+        CreateExternalTable, DFParser, FileType, QuerySettings, Statement as DFStatement,
+    },
 };
 
+use arrow::compute::can_cast_types;
 use arrow::datatypes::*;
+use chrono::{NaiveDate, NaiveTime, Timelike};
 
 use super::parser::ExplainPlan;
 use sqlparser::ast::{
-    BinaryOperator, DataType as SQLDataType, Expr as SQLExpr, Query, Select, SelectItem,
+    Assignment, BinaryOperator, DataType as SQLDataType, Expr as SQLExpr, Ident,
+    JoinConstraint, JoinOperator, ObjectName, ObjectType, Query, Select, SelectItem,
     SetExpr, TableFactor, TableWithJoins, UnaryOperator, Value,
 };
 use sqlparser::ast::{ColumnDef as SQLColumnDef, ColumnOption};
 use sqlparser::ast::{OrderByExpr, Statement};
+use sqlparser::dialect::{Dialect, GenericDialect};
 
 /// The SchemaProvider trait allows the query planner to obtain meta-data about tables and
 /// functions referenced in SQL statements
@@ -54,17 +67,265 @@ pub trait SchemaProvider {
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>>;
     /// Getter for a UDAF description
     fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>>;
+    /// Names of all the tables known to the provider, used in error messages
+    fn table_names(&self) -> Vec<String> {
+        vec![]
+    }
+    /// Getter for the data type of a `@name` or `@@name` variable, or `None` if
+    /// the variable is unknown.
+    ///
+    /// The default returns `None`, so the planner rejects every variable:
+    /// providers that support variables must override this method.
+    fn get_variable_meta(&self, _names: &[String]) -> Option<DataType> {
+        None
+    }
+    /// Whether the catalog `name` of a three-part table name such as
+    /// `catalog.schema.table` is known. No catalog is known by default.
+    fn has_catalog(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+/// The columns of a query, and the name qualifying them, that the correlated
+/// subqueries of its WHERE clause can reference
+#[derive(Clone, Copy)]
+pub struct QueryScope<'a> {
+    /// The schema of the relation the query's predicate is evaluated against
+    pub schema: &'a Schema,
+    /// The name of the single relation of the query's FROM clause, if any
+    pub relation_name: Option<&'a str>,
+}
+
+/// The scope in which SQL expressions are lowered into relational expressions
+pub struct PlannerContext<'a> {
+    /// The schema of the relation the expressions are evaluated against
+    pub schema: &'a Schema,
+    /// The contexts of the enclosing queries, innermost first, whose columns
+    /// can be referenced by the expressions of a correlated subquery
+    outer: Vec<PlannerContext<'a>>,
+    /// The visible fields by name, so that identifiers are resolved without
+    /// scanning the schemas
+    fields: HashMap<&'a str, &'a Field>,
+    /// The visible fields by lowercase name, to resolve unquoted identifiers
+    fields_by_lowercase_name: HashMap<String, &'a Field>,
+    /// The visible fields qualified by a table alias, such as `a.id`, by the
+    /// lowercase name of their column, to resolve unqualified identifiers
+    qualified_fields: HashMap<String, Vec<&'a Field>>,
+    /// The name of the single relation of the FROM clause, its alias or else its
+    /// table name, which can qualify the columns of the schema
+    relation_name: Option<&'a str>,
+}
+
+impl<'a> PlannerContext<'a> {
+    /// Create a context evaluating expressions against `schema`
+    pub fn new(schema: &'a Schema) -> Self {
+        let mut ctx = Self {
+            schema,
+            outer: vec![],
+            fields: HashMap::with_capacity(schema.fields().len()),
+            fields_by_lowercase_name: HashMap::with_capacity(schema.fields().len()),
+            qualified_fields: HashMap::new(),
+            relation_name: None,
+        };
+        ctx.add_fields(schema);
+        ctx
+    }
+
+    /// Make the fields of `schema` visible, unless a field of the same name already is
+    fn add_fields(&mut self, schema: &'a Schema) {
+        for field in schema.fields() {
+            // like `Schema::field_with_name`, the first field of a name wins
+            self.fields.entry(field.name().as_str()).or_insert(field);
+            self.fields_by_lowercase_name
+                .entry(field.name().to_lowercase())
+                .or_insert(field);
+            if let Some(i) = field.name().find('.') {
+                self.qualified_fields
+                    .entry(field.name()[i + 1..].to_lowercase())
+                    .or_insert_with(Vec::new)
+                    .push(field);
+            }
+        }
+    }
+
+    /// Make the columns of the enclosing queries `scopes`, innermost first,
+    /// visible when they are not shadowed by the ones of the context's schema
+    pub fn with_outer_scopes(mut self, scopes: &[QueryScope<'a>]) -> Self {
+        self.outer = scopes
+            .iter()
+            .map(|scope| {
+                PlannerContext::new(scope.schema).with_relation_name(scope.relation_name)
+            })
+            .collect();
+        self
+    }
+
+    /// Returns the scopes a subquery of an expression of this context is
+    /// planned in: this context's, followed by the ones of the enclosing queries
+    pub fn subquery_scopes(&self) -> Vec<QueryScope<'a>> {
+        std::iter::once(self)
+            .chain(self.outer.iter())
+            .map(|ctx| QueryScope {
+                schema: ctx.schema,
+                relation_name: ctx.relation_name,
+            })
+            .collect()
+    }
+
+    /// Resolve compound identifiers such as `p.age`, whose first part is
+    /// `relation_name`, against the columns of the schema
+    pub fn with_relation_name(mut self, relation_name: Option<&'a str>) -> Self {
+        self.relation_name = relation_name;
+        self
+    }
+
+    /// Returns the field named `name` of the schema. An unqualified name also
+    /// matches a field qualified by a table alias, as long as a single one has
+    /// that column name.
+    pub fn field_with_name(&self, name: &str) -> Option<&Field> {
+        self.fields
+            .get(name)
+            .copied()
+            .or_else(|| self.qualified_field(name, false))
+    }
+
+    /// Returns the only field qualified by a table alias whose column is `name`
+    fn qualified_field(&self, name: &str, case_insensitive: bool) -> Option<&Field> {
+        let candidates: Vec<&Field> = self
+            .qualified_fields
+            .get(&name.to_lowercase())?
+            .iter()
+            .copied()
+            .filter(|f| case_insensitive || f.name().ends_with(&format!(".{}", name)))
+            .collect();
+        match candidates.as_slice() {
+            [field] => Some(field),
+            _ => None,
+        }
+    }
+
+    /// Returns the field referenced by the identifier `id`. Quoted identifiers match
+    /// the field names exactly, unquoted ones also match them ignoring the case
+    /// when `case_insensitive` is set.
+    pub fn field_with_ident(&self, id: &Ident, case_insensitive: bool) -> Option<&Field> {
+        self.field_with_name(&id.value).or_else(|| {
+            if case_insensitive && id.quote_style.is_none() {
+                self.fields_by_lowercase_name
+                    .get(&id.value.to_lowercase())
+                    .copied()
+                    .or_else(|| self.qualified_field(&id.value, true))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns the field referenced by a compound identifier such as `a.id`,
+    /// whose first part is a table alias, or the name of the relation
+    pub fn field_with_compound_ident(
+        &self,
+        ids: &[Ident],
+        case_insensitive: bool,
+    ) -> Option<&Field> {
+        let id = Ident {
+            value: ids
+                .iter()
+                .map(|id| id.value.as_str())
+                .collect::<Vec<_>>()
+                .join("."),
+            quote_style: ids.iter().filter_map(|id| id.quote_style).next(),
+        };
+        self.field_with_ident(&id, case_insensitive).or_else(|| {
+            match (self.relation_name, ids) {
+                (Some(relation_name), [qualifier, column])
+                    if qualifier.value == relation_name
+                        || (case_insensitive
+                            && qualifier.quote_style.is_none()
+                            && qualifier.value.to_lowercase()
+                                == relation_name.to_lowercase()) =>
+                {
+                    self.field_with_ident(column, case_insensitive)
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Returns the field of the innermost enclosing query referenced by the
+    /// identifier `id`, or by the compound identifier `ids` when it has several
+    /// parts, for a correlated reference
+    pub fn outer_field_with_ident(
+        &self,
+        ids: &[Ident],
+        case_insensitive: bool,
+    ) -> Option<&Field> {
+        self.outer.iter().find_map(|ctx| match ids {
+            [id] => ctx.field_with_ident(id, case_insensitive),
+            _ => ctx.field_with_compound_ident(ids, case_insensitive),
+        })
+    }
 }
 
 /// SQL query planner
 pub struct SqlToRel<'a, S: SchemaProvider> {
     schema_provider: &'a S,
+    rewrite_null_comparisons: bool,
+    case_insensitive_identifiers: bool,
+    default_nulls_first: bool,
+    where_alias_references: bool,
+    warnings: RefCell<Vec<String>>,
+    /// Set once the error being returned names the expression it was raised in
+    error_has_expr_context: Cell<bool>,
 }
 
 impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
     /// Create a new query planner
     pub fn new(schema_provider: &'a S) -> Self {
-        SqlToRel { schema_provider }
+        SqlToRel {
+            schema_provider,
+            rewrite_null_comparisons: false,
+            case_insensitive_identifiers: true,
+            default_nulls_first: true,
+            where_alias_references: false,
+            warnings: RefCell::new(vec![]),
+            error_has_expr_context: Cell::new(false),
+        }
+    }
+
+    /// Match unquoted identifiers with the column names ignoring the case. Quoted
+    /// identifiers always match exactly. Enabled by default.
+    pub fn with_case_insensitive_identifiers(mut self, enabled: bool) -> Self {
+        self.case_insensitive_identifiers = enabled;
+        self
+    }
+
+    /// Rewrite `expr = NULL` and `expr != NULL` to `expr IS NULL` and
+    /// `expr IS NOT NULL` (MySQL compatibility). Disabled by default.
+    pub fn with_rewrite_null_comparisons(mut self, enabled: bool) -> Self {
+        self.rewrite_null_comparisons = enabled;
+        self
+    }
+
+    /// Sort NULLs before other values when an ORDER BY expression specifies neither
+    /// `NULLS FIRST` nor `NULLS LAST`. Enabled by default, to be consistent with
+    /// Spark.
+    pub fn with_default_nulls_first(mut self, enabled: bool) -> Self {
+        self.default_nulls_first = enabled;
+        self
+    }
+
+    /// Resolve names in WHERE that are not columns of the input to the aliased
+    /// expressions of the projection, as in `SELECT age * 2 AS d FROM person
+    /// WHERE d > 10` (MySQL compatibility). Disabled by default, as standard SQL
+    /// evaluates WHERE before the projection.
+    pub fn with_where_alias_references(mut self, enabled: bool) -> Self {
+        self.where_alias_references = enabled;
+        self
+    }
+
+    /// Warnings emitted while planning, such as rewritten NULL comparisons
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.borrow().clone()
     }
 
     /// Generate a logical plan from an DataFusion SQL statement
@@ -73,13 +334,82 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
             DFStatement::CreateExternalTable(s) => self.external_table_to_plan(&s),
             DFStatement::Statement(s) => self.sql_statement_to_plan(&s),
             DFStatement::Explain(s) => self.explain_statement_to_plan(&(*s)),
+            DFStatement::Settings(s) => self.settings_to_plan(&s),
+            DFStatement::DescribeTable(s) => self.describe_table_to_plan(&s.name),
+        }
+    }
+
+    /// Parse `sql` and generate a logical plan from its first statement
+    pub fn sql_to_plan(&self, sql: &str) -> Result<LogicalPlan> {
+        self.sql_to_plan_with_dialect(sql, &GenericDialect {})
+    }
+
+    /// Parse `sql` with `dialect`, which determines for instance how identifiers
+    /// are quoted, and generate a logical plan from its first statement
+    pub fn sql_to_plan_with_dialect(
+        &self,
+        sql: &str,
+        dialect: &dyn Dialect,
+    ) -> Result<LogicalPlan> {
+        let statements = DFParser::parse_sql_with_dialect(sql, dialect)
+            .map_err(|e| DataFusionError::Plan(format!("{:?}", e)))?;
+        match statements.first() {
+            Some(statement) => self.statement_to_plan(statement),
+            None => Err(DataFusionError::Plan(
+                "No SQL statement to plan in an empty input".to_string(),
+            )),
         }
     }
 
+    /// Generate a logical plan for each of `statements`, in order. Planning stops at
+    /// the first statement that fails, whose index is reported in the error.
+    pub fn statements_to_plans(
+        &self,
+        statements: &[DFStatement],
+    ) -> Result<Vec<LogicalPlan>> {
+        statements
+            .iter()
+            .enumerate()
+            .map(|(i, statement)| {
+                self.statement_to_plan(statement)
+                    .map_err(|e| DataFusionError::Plan(format!("Statement {}: {}", i, e)))
+            })
+            .collect()
+    }
+
     /// Generate a logical plan from an SQL statement
     pub fn sql_statement_to_plan(&self, sql: &Statement) -> Result<LogicalPlan> {
         match sql {
             Statement::Query(query) => self.query_to_plan(&query),
+            Statement::Insert { .. } => unsupported_statement("INSERT"),
+            Statement::Update {
+                table_name,
+                assignments,
+                selection,
+            } => self.update_to_plan(table_name, assignments, selection),
+            Statement::Delete { .. } => unsupported_statement("DELETE"),
+            Statement::Copy { .. } => unsupported_statement("COPY"),
+            Statement::CreateView { .. } => unsupported_statement("CREATE VIEW"),
+            Statement::AlterTable { .. } => unsupported_statement("ALTER TABLE"),
+            Statement::Drop {
+                object_type: ObjectType::Table,
+                if_exists,
+                names,
+                ..
+            } => self.drop_table_to_plan(names, *if_exists),
+            Statement::Drop { .. } => unsupported_statement("DROP"),
+            Statement::ShowColumns {
+                table_name,
+                filter: None,
+                ..
+            } => self.describe_table_to_plan(&table_name.to_string()),
+            Statement::ShowColumns { .. } => {
+                unsupported_statement("SHOW COLUMNS with a LIKE or WHERE filter")
+            }
+            Statement::StartTransaction { .. }
+            | Statement::SetTransaction { .. }
+            | Statement::Commit { .. }
+            | Statement::Rollback { .. } => unsupported_statement("Transaction control"),
             _ => Err(DataFusionError::NotImplemented(
                 "Only SELECT statements are implemented".to_string(),
             )),
@@ -88,8 +418,28 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
 
     /// Generate a logic plan from an SQL query
     pub fn query_to_plan(&self, query: &Query) -> Result<LogicalPlan> {
+        self.query_to_plan_with_outer_scopes(query, &[])
+    }
+
+    /// Returns the names of the base tables referenced by `query`, including the ones
+    /// referenced from joins, derived tables, common table expressions and subqueries.
+    /// References to common table expressions are not base tables and are omitted.
+    pub fn referenced_tables(&self, query: &Query) -> Result<Vec<String>> {
+        let mut tables = vec![];
+        collect_query_tables(query, &[], &mut tables);
+        Ok(tables)
+    }
+
+    /// Generate a logic plan from an SQL query whose predicates may reference
+    /// columns of the enclosing queries `outer_scopes`, innermost first, as in
+    /// correlated subqueries
+    fn query_to_plan_with_outer_scopes(
+        &self,
+        query: &Query,
+        outer_scopes: &[QueryScope],
+    ) -> Result<LogicalPlan> {
         let plan = match &query.body {
-            SetExpr::Select(s) => self.select_to_plan(s.as_ref()),
+            SetExpr::Select(s) => self.select_to_plan(s.as_ref(), outer_scopes),
             _ => Err(DataFusionError::NotImplemented(
                 format!("Query {} not implemented yet", query.body).to_owned(),
             )),
@@ -97,7 +447,36 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
 
         let plan = self.order_by(&plan, &query.order_by)?;
 
-        self.limit(&plan, &query.limit)
+        // the rows are skipped before the limit applies
+        let plan = self.offset(&plan, &query.offset)?;
+
+        // `FETCH FIRST n ROWS ONLY` is the standard spelling of `LIMIT n`
+        let limit = match (&query.limit, &query.fetch) {
+            (Some(_), Some(_)) => {
+                return Err(DataFusionError::Plan(
+                    "LIMIT and FETCH cannot be used in the same query".to_string(),
+                ))
+            }
+            (_, Some(fetch)) if fetch.with_ties => {
+                return Err(DataFusionError::NotImplemented(
+                    "FETCH ... WITH TIES is not supported".to_string(),
+                ))
+            }
+            (_, Some(fetch)) if fetch.percent => {
+                return Err(DataFusionError::NotImplemented(
+                    "FETCH ... PERCENT is not supported".to_string(),
+                ))
+            }
+            // without a quantity, a single row is fetched
+            (_, Some(fetch)) => Some(
+                fetch
+                    .quantity
+                    .clone()
+                    .unwrap_or_else(|| SQLExpr::Value(Value::Number("1".to_string()))),
+            ),
+            (limit, None) => limit.clone(),
+        };
+        self.limit(&plan, &limit)
     }
 
     /// Generate a logical plan from a CREATE EXTERNAL TABLE statement
@@ -110,7 +489,11 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
             columns,
             file_type,
             has_header,
+            delimiter,
+            compression,
+            partition_columns,
             location,
+            if_not_exists,
         } = statement;
 
         // semantic checks
@@ -130,10 +513,63 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                     ));
                 }
             }
+            FileType::Avro => {
+                if !columns.is_empty() {
+                    return Err(DataFusionError::Plan(
+                        "Column definitions can not be specified for AVRO files.".into(),
+                    ));
+                }
+            }
+            // the schema of JSON files may be given or inferred
             FileType::NdJson => {}
         };
+        if delimiter.is_some() && *file_type != FileType::CSV {
+            return Err(DataFusionError::Plan(
+                "A delimiter can only be specified for CSV files.".into(),
+            ));
+        }
+        // string literals are not unescaped by the tokenizer, so `'\t'` denotes a tab
+        let delimiter = match delimiter.as_ref().map(|d| d.as_str()) {
+            None => b',',
+            Some("\\t") => b'\t',
+            Some(d) if d.len() == 1 => d.as_bytes()[0],
+            Some(d) => {
+                return Err(DataFusionError::Plan(format!(
+                    "The delimiter must be a single character, found '{}'",
+                    d
+                )))
+            }
+        };
+        let compression = match compression.as_ref().map(|c| c.to_lowercase()) {
+            None => None,
+            Some(c) if c == "none" => None,
+            Some(c) if c == "gzip" || c == "zstd" || c == "snappy" => Some(c),
+            Some(c) => {
+                return Err(DataFusionError::Plan(format!(
+                    "Unsupported compression codec '{}', expected one of none, \
+                     gzip, zstd or snappy",
+                    c
+                )))
+            }
+        };
 
-        let schema = SchemaRef::new(self.build_schema(&columns)?);
+        // partition columns that are not declared are read from the partition
+        // directory names, as strings
+        let mut schema = self.build_schema(&columns)?;
+        for (i, column) in partition_columns.iter().enumerate() {
+            if partition_columns[..i].contains(column) {
+                return Err(DataFusionError::Plan(format!(
+                    "Partition column {} is specified more than once",
+                    column
+                )));
+            }
+            if schema.field_with_name(column).is_err() {
+                let mut fields = schema.fields().clone();
+                fields.push(Field::new(column, DataType::Utf8, false));
+                schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+            }
+        }
+        let schema = SchemaRef::new(schema);
 
         Ok(LogicalPlan::CreateExternalTable {
             schema,
@@ -141,9 +577,123 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
             location: location.clone(),
             file_type: file_type.clone(),
             has_header: has_header.clone(),
+            delimiter,
+            compression,
+            partition_columns: partition_columns.clone(),
+            if_not_exists: *if_not_exists,
+        })
+    }
+
+    /// Generate a logical plan from a DROP TABLE statement
+    fn drop_table_to_plan(
+        &self,
+        names: &[ObjectName],
+        if_exists: bool,
+    ) -> Result<LogicalPlan> {
+        if names.len() != 1 {
+            return Err(DataFusionError::NotImplemented(
+                "DROP TABLE of several tables is not supported".to_string(),
+            ));
+        }
+        let name = names[0].to_string();
+        if !if_exists && self.schema_provider.get_table_meta(&name).is_none() {
+            return Err(DataFusionError::Plan(format!(
+                "Table {} does not exist{}",
+                name,
+                self.available_tables_hint(&name)
+            )));
+        }
+        Ok(LogicalPlan::DropTable {
+            schema: SchemaRef::new(Schema::empty()),
+            name,
+            if_exists,
         })
     }
 
+    /// Generate a plan for UPDATE: the rows of the table selected by the WHERE
+    /// clause, and the new values of the assigned columns, cast to their types
+    fn update_to_plan(
+        &self,
+        table_name: &ObjectName,
+        assignments: &[Assignment],
+        selection: &Option<SQLExpr>,
+    ) -> Result<LogicalPlan> {
+        let name = table_name.to_string();
+        let table_schema = match self.schema_provider.get_table_meta(&name) {
+            Some(schema) => schema,
+            None => {
+                return Err(DataFusionError::Plan(format!(
+                    "no schema found for table {}{}",
+                    name,
+                    self.available_tables_hint(&name)
+                )))
+            }
+        };
+        let schema_name = match table_name.0.as_slice() {
+            [.., schema, _] => schema.value.clone(),
+            _ => "default".to_string(),
+        };
+        let plan =
+            LogicalPlanBuilder::scan(&schema_name, &name, table_schema.as_ref(), None)?
+                .build()?;
+        let plan = self.filter(&plan, selection, &[], Some(&name), None)?;
+
+        // the assigned columns are resolved like the columns of the WHERE clause
+        let table_ctx = PlannerContext::new(table_schema.as_ref());
+        let mut columns: Vec<(String, Expr)> = Vec::with_capacity(assignments.len());
+        for assignment in assignments {
+            let field = table_ctx
+                .field_with_ident(&assignment.id, self.case_insensitive_identifiers)
+                .ok_or_else(|| {
+                    DataFusionError::Plan(format!(
+                        "Unknown column '{}' in UPDATE of table {}",
+                        assignment.id.value, name
+                    ))
+                })?;
+            let column = field.name();
+            if columns.iter().any(|(assigned, _)| assigned == column) {
+                return Err(DataFusionError::Plan(format!(
+                    "Column '{}' is assigned more than once in UPDATE of table {}",
+                    column, name
+                )));
+            }
+            let value = self.sql_to_rex(&assignment.value, plan.schema())?;
+            let value_type = value.get_type(plan.schema())?;
+            if !can_cast_types(&value_type, field.data_type()) {
+                return Err(DataFusionError::Plan(format!(
+                    "Cannot assign {:?} to column '{}' of type {:?}",
+                    value_type,
+                    column,
+                    field.data_type()
+                )));
+            }
+            let value = value.cast_to(field.data_type(), plan.schema())?;
+            columns.push((column.clone(), value));
+        }
+        Ok(LogicalPlan::Update {
+            table_name: name,
+            assignments: columns,
+            input: Arc::new(plan),
+            schema: SchemaRef::new(Schema::empty()),
+        })
+    }
+
+    /// Generate a plan listing the columns of a table, for DESCRIBE and SHOW COLUMNS
+    fn describe_table_to_plan(&self, name: &str) -> Result<LogicalPlan> {
+        match self.schema_provider.get_table_meta(name) {
+            Some(table_schema) => Ok(LogicalPlan::DescribeTable {
+                name: name.to_string(),
+                table_schema,
+                schema: LogicalPlan::describe_schema(),
+            }),
+            None => Err(DataFusionError::Plan(format!(
+                "Table {} does not exist{}",
+                name,
+                self.available_tables_hint(name)
+            ))),
+        }
+    }
+
     /// Generate a plan for EXPLAIN ... that will print out a plan
     ///
     pub fn explain_statement_to_plan(
@@ -153,6 +703,14 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
         let verbose = explain_plan.verbose;
         let plan = self.statement_to_plan(&explain_plan.statement)?;
 
+        if explain_plan.analyze {
+            return Ok(LogicalPlan::Analyze {
+                verbose,
+                input: Arc::new(plan),
+                schema: LogicalPlan::explain_schema(),
+            });
+        }
+
         let stringified_plans = vec![StringifiedPlan::new(
             PlanType::LogicalPlan,
             format!("{:#?}", plan),
@@ -169,8 +727,37 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
         })
     }
 
+    /// Generate a plan for a query with query-level settings. Recognized settings
+    /// are captured on the plan, unknown ones are ignored with a warning, which
+    /// `ExecutionContext::create_logical_plan_with_warnings` returns.
+    pub fn settings_to_plan(&self, settings: &QuerySettings) -> Result<LogicalPlan> {
+        let plan = self.statement_to_plan(&settings.statement)?;
+
+        let mut captured = BTreeMap::new();
+        for (name, value) in &settings.settings {
+            let name = name.to_lowercase();
+            if !QUERY_SETTINGS.contains(&name.as_str()) {
+                self.warnings
+                    .borrow_mut()
+                    .push(format!("Ignoring unknown setting '{}'", name));
+                continue;
+            }
+            let value = match value {
+                Value::SingleQuotedString(s) => s.clone(),
+                other => other.to_string(),
+            };
+            captured.insert(name, value);
+        }
+
+        Ok(LogicalPlan::Settings {
+            settings: captured,
+            input: Arc::new(plan),
+        })
+    }
+
     fn build_schema(&self, columns: &Vec<SQLColumnDef>) -> Result<Schema> {
         let mut fields = Vec::new();
+        let mut metadata = HashMap::new();
 
         for column in columns {
             let data_type = self.make_data_type(&column.data_type)?;
@@ -179,9 +766,13 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                 .iter()
                 .any(|x| x.option == ColumnOption::Null);
             fields.push(Field::new(&column.name.value, data_type, allow_null));
+            // the length of a CHAR(n) column is not part of its Utf8 type
+            if let SQLDataType::Char(Some(length)) = column.data_type {
+                metadata.insert(char_length_key(&column.name.value), length.to_string());
+            }
         }
 
-        Ok(Schema::new(fields))
+        Ok(Schema::new_with_metadata(fields, metadata))
     }
 
     /// Maps the SQL type to the corresponding Arrow `DataType`
@@ -216,98 +807,534 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                 "FROM with multiple tables is still not implemented".to_string(),
             ));
         };
-        let relation = &from[0].relation;
-        match relation {
-            TableFactor::Table { name, .. } => {
+        // the columns of the inputs of a join are qualified by their table alias,
+        // so that the same table can be joined with itself
+        let qualify = !from[0].joins.is_empty();
+        let mut plan = self.create_relation(&from[0].relation, qualify)?;
+        for join in &from[0].joins {
+            let right = self.create_relation(&join.relation, qualify)?;
+            plan = self.join_to_plan(&plan, &right, &join.join_operator)?;
+        }
+        Ok(plan)
+    }
+
+    /// Generate a logical plan joining `left` and `right`
+    fn join_to_plan(
+        &self,
+        left: &LogicalPlan,
+        right: &LogicalPlan,
+        join_operator: &JoinOperator,
+    ) -> Result<LogicalPlan> {
+        let (join_type, constraint) = match join_operator {
+            JoinOperator::Inner(constraint) => (JoinType::Inner, constraint),
+            JoinOperator::LeftOuter(constraint) => (JoinType::Left, constraint),
+            JoinOperator::RightOuter(constraint) => (JoinType::Right, constraint),
+            JoinOperator::FullOuter(constraint) => (JoinType::Full, constraint),
+            _ => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Unsupported join operator {:?}",
+                    join_operator
+                )))
+            }
+        };
+        let keys: Vec<String> = match constraint {
+            JoinConstraint::Using(idents) => {
+                idents.iter().map(|ident| ident.value.clone()).collect()
+            }
+            // a natural join is a join using all the columns the inputs have in common
+            JoinConstraint::Natural => {
+                let keys: Vec<String> = left
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|f| f.name().clone())
+                    .filter(|name| right.schema().field_with_name(name).is_ok())
+                    .collect();
+                if keys.is_empty() {
+                    return Err(DataFusionError::Plan(
+                        "NATURAL JOIN requires the inputs to have columns in common"
+                            .to_string(),
+                    ));
+                }
+                keys
+            }
+            JoinConstraint::On(expr) => {
+                return self.join_on_to_plan(left, right, join_type, expr)
+            }
+        };
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        LogicalPlanBuilder::from(left)
+            .join(right, join_type, &keys, &keys)?
+            .build()
+    }
+
+    /// Generate a logical plan of a join whose condition is `on`. The equalities
+    /// between a column of each input become the keys of the join, the other
+    /// predicates filter the joined rows.
+    fn join_on_to_plan(
+        &self,
+        left: &LogicalPlan,
+        right: &LogicalPlan,
+        join_type: JoinType,
+        on: &SQLExpr,
+    ) -> Result<LogicalPlan> {
+        let left_ctx = PlannerContext::new(left.schema());
+        let right_ctx = PlannerContext::new(right.schema());
+        let key_name = |ctx: &PlannerContext, expr: &SQLExpr| match expr {
+            SQLExpr::Identifier(id) => ctx
+                .field_with_ident(id, self.case_insensitive_identifiers)
+                .map(|f| f.name().clone()),
+            SQLExpr::CompoundIdentifier(ids) => ctx
+                .field_with_compound_ident(ids, self.case_insensitive_identifiers)
+                .map(|f| f.name().clone()),
+            _ => None,
+        };
+
+        let mut conjuncts = vec![];
+        split_conjunction(on, &mut conjuncts);
+        let mut left_keys = vec![];
+        let mut right_keys = vec![];
+        let mut residual = vec![];
+        for conjunct in conjuncts {
+            let key = match conjunct {
+                SQLExpr::BinaryOp {
+                    left: l,
+                    op: BinaryOperator::Eq,
+                    right: r,
+                } => match (key_name(&left_ctx, l), key_name(&right_ctx, r)) {
+                    (Some(l), Some(r)) => Some((l, r)),
+                    _ => match (key_name(&left_ctx, r), key_name(&right_ctx, l)) {
+                        (Some(l), Some(r)) => Some((l, r)),
+                        _ => None,
+                    },
+                },
+                _ => None,
+            };
+            match key {
+                Some((l, r)) => {
+                    left_keys.push(l);
+                    right_keys.push(r);
+                }
+                None => residual.push(conjunct),
+            }
+        }
+
+        if left_keys.is_empty() {
+            return Err(DataFusionError::NotImplemented(
+                "JOIN ... ON without an equality between columns of both inputs is not supported yet"
+                    .to_string(),
+            ));
+        }
+        // a predicate of an outer join decides which rows match, it does not
+        // remove the rows of the preserved input, so it is not a filter
+        if !residual.is_empty() && join_type != JoinType::Inner {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Only equalities between columns are supported in the ON clause of a {:?} join",
+                join_type
+            )));
+        }
+
+        let left_keys: Vec<&str> = left_keys.iter().map(|key| key.as_str()).collect();
+        let right_keys: Vec<&str> = right_keys.iter().map(|key| key.as_str()).collect();
+        let join = LogicalPlanBuilder::from(left)
+            .join(right, join_type, &left_keys, &right_keys)?
+            .build()?;
+        if residual.is_empty() {
+            return Ok(join);
+        }
+
+        let ctx = PlannerContext::new(join.schema());
+        let mut predicate = self.clause_expr_to_rex(residual[0], &ctx)?;
+        for conjunct in &residual[1..] {
+            predicate = predicate.and(self.clause_expr_to_rex(conjunct, &ctx)?);
+        }
+        LogicalPlanBuilder::from(&join).filter(predicate)?.build()
+    }
+
+    /// Generate a logical plan from a table factor of the FROM clause. The columns
+    /// are renamed by the column list of the alias, if any, and when `qualify` is
+    /// set the columns of an aliased relation are named `alias.column`.
+    fn create_relation(
+        &self,
+        relation: &TableFactor,
+        qualify: bool,
+    ) -> Result<LogicalPlan> {
+        let (plan, alias) = match relation {
+            TableFactor::Table { name, alias, .. } => {
+                // the table is looked up by its qualified name, and the scan
+                // records the schema it belongs to
+                let schema_name = match name.0.as_slice() {
+                    [_] => "default".to_string(),
+                    [schema, _] => schema.value.clone(),
+                    [catalog, schema, _] => {
+                        if !self.schema_provider.has_catalog(&catalog.value) {
+                            return Err(DataFusionError::Plan(format!(
+                                "no catalog found named {}",
+                                catalog.value
+                            )));
+                        }
+                        schema.value.clone()
+                    }
+                    _ => {
+                        return Err(DataFusionError::NotImplemented(format!(
+                            "Table name {} has too many parts, expected \
+                             [[catalog.]schema.]table",
+                            name
+                        )))
+                    }
+                };
                 let name = name.to_string();
                 match self.schema_provider.get_table_meta(&name) {
-                    Some(schema) => Ok(LogicalPlanBuilder::scan(
-                        "default",
-                        &name,
-                        schema.as_ref(),
-                        None,
-                    )?
-                    .build()?),
-                    None => Err(DataFusionError::Plan(format!(
-                        "no schema found for table {}",
-                        name
-                    ))),
+                    Some(schema) => (
+                        LogicalPlanBuilder::scan(
+                            &schema_name,
+                            &name,
+                            schema.as_ref(),
+                            None,
+                        )?
+                        .build()?,
+                        alias,
+                    ),
+                    None => {
+                        return Err(DataFusionError::Plan(format!(
+                            "no schema found for table {}{}",
+                            name,
+                            self.available_tables_hint(&name)
+                        )))
+                    }
                 }
             }
-            _ => Err(DataFusionError::NotImplemented(
-                "Subqueries are still not supported".to_string(),
-            )),
+            // the output schema of a derived table is the one of its query,
+            // including the aliases of its projection
+            TableFactor::Derived {
+                lateral: false,
+                subquery,
+                alias,
+                ..
+            } => (self.query_to_plan(subquery)?, alias),
+            _ => {
+                return Err(DataFusionError::NotImplemented(
+                    "Subqueries are still not supported".to_string(),
+                ))
+            }
+        };
+        let alias = match alias {
+            Some(alias) if qualify || !alias.columns.is_empty() => alias,
+            _ => return Ok(plan),
+        };
+        let fields = plan.schema().fields();
+        if !alias.columns.is_empty() && alias.columns.len() != fields.len() {
+            return Err(DataFusionError::Plan(format!(
+                "The alias {} names {} columns but the relation has {} columns",
+                alias.name,
+                alias.columns.len(),
+                fields.len()
+            )));
         }
+        // the columns are renamed positionally by the column list of the alias
+        let exprs: Vec<Expr> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let column = match alias.columns.get(i) {
+                    Some(column) => &column.value,
+                    None => f.name(),
+                };
+                let name = if qualify {
+                    format!("{}.{}", alias.name.value, column)
+                } else {
+                    column.clone()
+                };
+                col(f.name()).alias(&name)
+            })
+            .collect();
+        LogicalPlanBuilder::from(&plan).project(exprs)?.build()
     }
 
-    /// Generate a logic plan from an SQL select
-    fn select_to_plan(&self, select: &Select) -> Result<LogicalPlan> {
-        if select.having.is_some() {
-            return Err(DataFusionError::NotImplemented(
-                "HAVING is not implemented yet".to_string(),
-            ));
+    /// Returns a hint suggesting the tables whose names are the closest to `name`,
+    /// followed by the list of all the tables of the schema provider
+    fn available_tables_hint(&self, name: &str) -> String {
+        let mut table_names = self.schema_provider.table_names();
+        if table_names.is_empty() {
+            return "".to_string();
         }
+        table_names.sort();
+        format!(
+            "{}; available tables are: {}",
+            suggest_names(name, table_names.iter().map(|n| n.as_str())),
+            table_names.join(", ")
+        )
+    }
 
+    /// Generate a logic plan from an SQL select
+    fn select_to_plan(
+        &self,
+        select: &Select,
+        outer_scopes: &[QueryScope],
+    ) -> Result<LogicalPlan> {
         let plan = self.from_join_to_plan(&select.from)?;
 
-        // filter (also known as selection) first
-        let plan = self.filter(&plan, &select.selection)?;
-
+        // the projection is computed over the same columns as the filter
+        let relation_name = single_relation_name(&select.from);
+        let ctx = PlannerContext::new(plan.schema()).with_relation_name(relation_name);
         let projection_expr: Vec<Expr> = select
             .projection
             .iter()
-            .map(|e| self.sql_select_to_rex(&e, &plan.schema()))
+            .map(|e| self.sql_select_to_rex(&e, &ctx))
             .collect::<Result<Vec<Expr>>>()?;
 
-        let aggr_expr: Vec<Expr> = projection_expr
+        // filter (also known as selection) first
+        let aliases = if self.where_alias_references {
+            Some(projection_expr.as_slice())
+        } else {
+            None
+        };
+        let plan = self.filter(
+            &plan,
+            &select.selection,
+            outer_scopes,
+            relation_name,
+            aliases,
+        )?;
+
+        let mut aggr_expr: Vec<Expr> = projection_expr
             .iter()
             .filter(|e| is_aggregate_expr(e))
             .map(|e| e.clone())
             .collect();
+        // the aggregates nested in other expressions of the projection, such as
+        // CAST(COUNT(*) AS FLOAT), are computed by the aggregation as well
+        for e in projection_expr.iter().filter(|e| !is_aggregate_expr(e)) {
+            for nested in find_aggregate_exprs(e) {
+                let name = format!("{:?}", nested);
+                if !aggr_expr
+                    .iter()
+                    .any(|a| format!("{:?}", unalias(a)) == name)
+                {
+                    aggr_expr.push(nested);
+                }
+            }
+        }
+
+        let window_expr = find_window_exprs(&projection_expr);
+
+        let having_expr = match select.having {
+            Some(ref having) => Some(self.aliased_clause_to_rex(
+                having,
+                &projection_expr,
+                &plan.schema(),
+                &[],
+                relation_name,
+            )?),
+            None => None,
+        };
 
         // apply projection or aggregate
-        let plan = if (select.group_by.len() > 0) | (aggr_expr.len() > 0) {
-            self.aggregate(&plan, projection_expr, &select.group_by, aggr_expr)?
+        let plan = if (select.group_by.len() > 0)
+            | (aggr_expr.len() > 0)
+            | having_expr.is_some()
+        {
+            if !window_expr.is_empty() {
+                return Err(DataFusionError::NotImplemented(
+                    "Window functions in aggregate queries are not supported yet"
+                        .to_string(),
+                ));
+            }
+            let plan =
+                self.aggregate(&plan, projection_expr, &select.group_by, aggr_expr)?;
+            match having_expr {
+                Some(having_expr) => self.having(&plan, &having_expr)?,
+                None => plan,
+            }
+        } else if !window_expr.is_empty() {
+            self.window(&plan, projection_expr, window_expr)?
         } else {
             self.project(&plan, projection_expr)?
         };
         Ok(plan)
     }
 
-    /// Apply a filter to the plan
+    /// Apply a filter to the plan. Identifiers that are not found in the plan's
+    /// schema are resolved against the aliases of `projection_expr`, if given,
+    /// and then against the enclosing queries `outer_scopes`.
     fn filter(
         &self,
         plan: &LogicalPlan,
         predicate: &Option<SQLExpr>,
+        outer_scopes: &[QueryScope],
+        relation_name: Option<&str>,
+        projection_expr: Option<&[Expr]>,
     ) -> Result<LogicalPlan> {
         match *predicate {
-            Some(ref predicate_expr) => LogicalPlanBuilder::from(&plan)
-                .filter(self.sql_to_rex(predicate_expr, &plan.schema())?)?
-                .build(),
+            Some(ref predicate_expr) => {
+                let predicate = match projection_expr {
+                    Some(projection_expr) => self.aliased_clause_to_rex(
+                        predicate_expr,
+                        projection_expr,
+                        plan.schema(),
+                        outer_scopes,
+                        relation_name,
+                    )?,
+                    None => {
+                        let ctx = PlannerContext::new(plan.schema())
+                            .with_outer_scopes(outer_scopes)
+                            .with_relation_name(relation_name);
+                        self.clause_expr_to_rex(predicate_expr, &ctx)?
+                    }
+                };
+                if contains_aggregate_expr(&predicate) {
+                    return Err(DataFusionError::Plan(
+                        "aggregate functions are not allowed in WHERE".to_string(),
+                    ));
+                }
+                LogicalPlanBuilder::from(&plan).filter(predicate)?.build()
+            }
             _ => Ok(plan.clone()),
         }
     }
 
+    /// Generate the relational expression of a clause over `schema`, such as
+    /// HAVING over the input of the aggregation. Names that are not columns of
+    /// `schema` refer to the aliased expressions of the projection.
+    fn aliased_clause_to_rex(
+        &self,
+        sql: &SQLExpr,
+        projection_expr: &[Expr],
+        schema: &Schema,
+        outer_scopes: &[QueryScope],
+        relation_name: Option<&str>,
+    ) -> Result<Expr> {
+        let aliases: Vec<(&String, &Expr)> = projection_expr
+            .iter()
+            .filter_map(|e| match e {
+                Expr::Alias(expr, alias) if schema.field_with_name(alias).is_err() => {
+                    Some((alias, expr.as_ref()))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut fields = schema.fields().clone();
+        for (alias, expr) in &aliases {
+            fields.push(Field::new(alias.as_str(), expr.get_type(schema)?, true));
+        }
+        let alias_schema = Schema::new(fields);
+        let ctx = PlannerContext::new(&alias_schema)
+            .with_outer_scopes(outer_scopes)
+            .with_relation_name(relation_name);
+        let expr = self.clause_expr_to_rex(sql, &ctx)?;
+        replace_aliases(&expr, &aliases)
+    }
+
+    /// Filter the output of the aggregation of `plan` by the HAVING predicate
+    /// `having_expr`, an expression over the input of the aggregation. Aggregates
+    /// of the predicate that are not selected are computed by the aggregation,
+    /// and projected away after filtering.
+    fn having(&self, plan: &LogicalPlan, having_expr: &Expr) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Projection { input, expr, .. } => {
+                let input = self.having(input, having_expr)?;
+                self.project(&input, expr.clone())
+            }
+            LogicalPlan::Aggregate {
+                input,
+                group_expr,
+                aggr_expr,
+                ..
+            } => {
+                let selected: Vec<String> = aggr_expr
+                    .iter()
+                    .map(|e| format!("{:?}", unalias(e)))
+                    .collect();
+                let missing: Vec<Expr> = find_aggregate_exprs(having_expr)
+                    .into_iter()
+                    .filter(|e| !selected.contains(&format!("{:?}", e)))
+                    .collect();
+
+                // the grouping expressions and aggregates, by the name of the
+                // column they are output as
+                let outputs = grouping_set_to_exprlist(group_expr)
+                    .iter()
+                    .chain(aggr_expr.iter())
+                    .chain(missing.iter())
+                    .map(|e| Ok((unalias(e).clone(), e.name(input.schema())?)))
+                    .collect::<Result<Vec<_>>>()?;
+                let predicate = rebase_aggregate_expr(having_expr, &outputs, "HAVING")?;
+
+                if missing.is_empty() {
+                    LogicalPlanBuilder::from(plan).filter(predicate)?.build()
+                } else {
+                    let columns = plan
+                        .schema()
+                        .fields()
+                        .iter()
+                        .map(|f| Expr::Column(f.name().clone()))
+                        .collect();
+                    let plan = add_aggregates(plan, missing)?;
+                    let plan =
+                        LogicalPlanBuilder::from(&plan).filter(predicate)?.build()?;
+                    self.project(&plan, columns)
+                }
+            }
+            _ => Err(DataFusionError::Internal(format!(
+                "Expected an aggregation, found {:?}",
+                plan
+            ))),
+        }
+    }
+
     /// Wrap a plan in a projection
     fn project(&self, input: &LogicalPlan, expr: Vec<Expr>) -> Result<LogicalPlan> {
         LogicalPlanBuilder::from(input).project(expr)?.build()
     }
 
-    /// Wrap a plan in an aggregate
-    fn aggregate(
+    /// Wrap a plan in a window and a projection that refers to the results of
+    /// the window functions by name
+    fn window(
         &self,
         input: &LogicalPlan,
         projection_expr: Vec<Expr>,
-        group_by: &Vec<SQLExpr>,
-        aggr_expr: Vec<Expr>,
+        window_expr: Vec<Expr>,
     ) -> Result<LogicalPlan> {
-        let group_expr: Vec<Expr> = group_by
+        let plan = LogicalPlanBuilder::from(input)
+            .window(window_expr.clone())?
+            .build()?;
+        let projection_expr = projection_expr
             .iter()
-            .map(|e| self.sql_to_rex(&e, &input.schema()))
-            .collect::<Result<Vec<Expr>>>()?;
+            .map(|e| rebase_expr(e, &window_expr, input.schema()))
+            .collect::<Result<Vec<_>>>()?;
+        self.project(&plan, projection_expr)
+    }
+
+    /// Wrap a plan in an aggregate
+    fn aggregate(
+        &self,
+        input: &LogicalPlan,
+        projection_expr: Vec<Expr>,
+        group_by: &Vec<SQLExpr>,
+        aggr_expr: Vec<Expr>,
+    ) -> Result<LogicalPlan> {
+        let group_expr = if is_group_by_all(group_by) {
+            // GROUP BY ALL groups by every non-aggregate expression of the projection
+            projection_expr
+                .iter()
+                .filter(|e| !contains_aggregate_expr(e))
+                .map(|e| match e {
+                    Expr::Alias(expr, _) => expr.as_ref().clone(),
+                    _ => e.clone(),
+                })
+                .collect()
+        } else {
+            self.group_by_to_rex(group_by, &projection_expr, &input.schema())?
+        };
+        let grouping_expr = grouping_set_to_exprlist(&group_expr);
 
-        let group_by_count = group_expr.len();
-        let aggr_count = aggr_expr.len();
+        let group_by_count = grouping_expr.len();
+        // the expressions of the projection computed from aggregates, either
+        // aggregates themselves or expressions over them
+        let aggr_count = projection_expr
+            .iter()
+            .filter(|e| contains_aggregate_expr(e))
+            .count();
 
         if group_by_count + aggr_count != projection_expr.len() {
             return Err(DataFusionError::Plan(
@@ -315,14 +1342,54 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
             ));
         }
 
+        // every non-aggregate expression of the projection must be a grouping
+        // expression, which is matched by name ignoring aliases
+        let group_names = grouping_expr
+            .iter()
+            .map(|e| unaliased_name(e, input.schema()))
+            .collect::<Result<Vec<_>>>()?;
+        for e in projection_expr
+            .iter()
+            .filter(|e| !contains_aggregate_expr(e))
+        {
+            if !group_names.contains(&unaliased_name(e, input.schema())?) {
+                return Err(DataFusionError::Plan(
+                    "Projection references non-aggregate values".to_owned(),
+                ));
+            }
+        }
+
+        // the grouping expressions and aggregates, by the name of the column
+        // they are output as
+        let outputs = grouping_expr
+            .iter()
+            .chain(aggr_expr.iter())
+            .map(|e| Ok((unalias(e).clone(), e.name(input.schema())?)))
+            .collect::<Result<Vec<_>>>()?;
+
         let plan = LogicalPlanBuilder::from(&input)
             .aggregate(group_expr, aggr_expr)?
             .build()?;
 
-        // optionally wrap in projection to preserve final order of fields
-        let expected_columns: Vec<String> = projection_expr
+        // optionally wrap in projection to preserve final order of fields.
+        // Aliased grouping expressions are output by the aggregate under their
+        // unaliased name, so the alias is re-applied here, and the expressions
+        // over aggregates are computed from the aggregated columns.
+        let final_expr = projection_expr
+            .iter()
+            .map(|e| match e {
+                _ if !is_aggregate_expr(e) && contains_aggregate_expr(e) => {
+                    rebase_aggregate_expr(e, &outputs, "SELECT")
+                }
+                Expr::Alias(expr, alias) if !is_aggregate_expr(expr) => {
+                    Ok(Expr::Column(expr.name(input.schema())?).alias(alias))
+                }
+                _ => Ok(Expr::Column(e.name(input.schema())?)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let expected_columns: Vec<String> = final_expr
             .iter()
-            .map(|e| e.name(input.schema()))
+            .map(|e| e.name(plan.schema()))
             .collect::<Result<Vec<_>>>()?;
         let columns: Vec<String> = plan
             .schema()
@@ -331,35 +1398,117 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
             .map(|f| f.name().clone())
             .collect::<Vec<_>>();
         if expected_columns != columns {
-            self.project(
-                &plan,
-                expected_columns
-                    .iter()
-                    .map(|c| Expr::Column(c.clone()))
-                    .collect(),
-            )
+            self.project(&plan, final_expr)
         } else {
             Ok(plan)
         }
     }
 
-    /// Wrap a plan in a limit
+    /// Generate the grouping expressions of a GROUP BY clause. `ROLLUP (a, b)` is
+    /// lowered into the grouping sets `(a, b), (a), ()`, which are combined with
+    /// the other expressions of the clause into a single `Expr::GroupingSet`.
+    /// A name that is not a column of the input refers to the expression of the
+    /// projection with that alias.
+    fn group_by_to_rex(
+        &self,
+        group_by: &[SQLExpr],
+        projection_expr: &[Expr],
+        schema: &Schema,
+    ) -> Result<Vec<Expr>> {
+        let mut group_expr = vec![];
+        let mut grouping_sets: Option<Vec<Vec<Expr>>> = None;
+        for e in group_by {
+            match e {
+                SQLExpr::Function(function)
+                    if function.name.to_string().to_uppercase() == "ROLLUP" =>
+                {
+                    let exprs = function
+                        .args
+                        .iter()
+                        .map(|a| self.sql_to_rex(a, schema))
+                        .collect::<Result<Vec<_>>>()?;
+                    let rollup: Vec<Vec<Expr>> = (0..=exprs.len())
+                        .rev()
+                        .map(|i| exprs[..i].to_vec())
+                        .collect();
+                    let sets = grouping_sets.unwrap_or_else(|| vec![vec![]]);
+                    grouping_sets = Some(
+                        sets.iter()
+                            .flat_map(|set| {
+                                rollup.iter().map(move |r| {
+                                    set.iter().chain(r.iter()).cloned().collect()
+                                })
+                            })
+                            .collect(),
+                    );
+                }
+                _ => group_expr.push(match self.sql_to_rex(e, schema) {
+                    Ok(expr) => expr,
+                    Err(err) => aliased_projection_expr(e, projection_expr).ok_or(err)?,
+                }),
+            }
+        }
+
+        Ok(match grouping_sets {
+            Some(sets) => vec![Expr::GroupingSet(
+                sets.into_iter()
+                    .map(|set| group_expr.iter().chain(set.iter()).cloned().collect())
+                    .collect(),
+            )],
+            None => group_expr,
+        })
+    }
+
+    /// Wrap a plan in a limit. The limit can be any constant integer expression,
+    /// such as `10 + 5`.
     fn limit(&self, input: &LogicalPlan, limit: &Option<SQLExpr>) -> Result<LogicalPlan> {
         match *limit {
             Some(ref limit_expr) => {
-                let n = match self.sql_to_rex(&limit_expr, &input.schema())? {
-                    Expr::Literal(ScalarValue::Int64(Some(n))) => Ok(n as usize),
-                    _ => Err(DataFusionError::Plan(
-                        "Unexpected expression for LIMIT clause".to_string(),
-                    )),
-                }?;
-
+                let n = self.row_count("LIMIT", limit_expr, input)?;
                 LogicalPlanBuilder::from(&input).limit(n)?.build()
             }
             _ => Ok(input.clone()),
         }
     }
 
+    /// Wrap a plan in an offset
+    fn offset(
+        &self,
+        input: &LogicalPlan,
+        offset: &Option<SQLExpr>,
+    ) -> Result<LogicalPlan> {
+        match *offset {
+            Some(ref offset_expr) => {
+                let n = self.row_count("OFFSET", offset_expr, input)?;
+                LogicalPlanBuilder::from(&input).offset(n)?.build()
+            }
+            _ => Ok(input.clone()),
+        }
+    }
+
+    /// Evaluates the row count of the `clause`, such as LIMIT, which must be a
+    /// non-negative constant integer expression
+    fn row_count(
+        &self,
+        clause: &str,
+        sql: &SQLExpr,
+        input: &LogicalPlan,
+    ) -> Result<usize> {
+        let expr = self.sql_to_rex(sql, &input.schema())?;
+        match eval_integer_expr(&expr) {
+            Some(n) if n >= 0 => Ok(n as usize),
+            Some(n) => Err(DataFusionError::Plan(format!(
+                "{} must not be negative, found {}",
+                clause, n
+            ))),
+            None => Err(DataFusionError::Plan(format!(
+                "Unexpected expression for {} clause: {:?}, \
+                 expected a constant integer expression",
+                clause, expr
+            ))),
+        }
+    }
+
     /// Wrap the logical in a sort
     fn order_by(
         &self,
@@ -370,16 +1519,29 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
             return Ok(plan.clone());
         }
 
-        let input_schema = plan.schema();
+        // aggregates that are sorted on but not selected are added to the
+        // aggregation, and projected away after sorting
+        let missing_aggr_expr = self.missing_order_by_aggregates(plan, order_by)?;
+        if !missing_aggr_expr.is_empty() {
+            let output_expr: Vec<Expr> = plan
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| Expr::Column(f.name().clone()))
+                .collect();
+            let plan = add_aggregates(plan, missing_aggr_expr)?;
+            let plan = self.order_by(&plan, order_by)?;
+            return self.project(&plan, output_expr);
+        }
+
         let order_by_rex: Result<Vec<Expr>> = order_by
             .iter()
             .map(|e| {
                 Ok(Expr::Sort {
-                    expr: Box::new(self.sql_to_rex(&e.expr, &input_schema).unwrap()),
+                    expr: Box::new(self.order_by_to_rex(&e.expr, plan)?),
                     // by default asc
                     asc: e.asc.unwrap_or(true),
-                    // by default nulls first to be consistent with spark
-                    nulls_first: e.nulls_first.unwrap_or(true),
+                    nulls_first: e.nulls_first.unwrap_or(self.default_nulls_first),
                 })
             })
             .collect();
@@ -387,12 +1549,76 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
         LogicalPlanBuilder::from(&plan).sort(order_by_rex?)?.build()
     }
 
+    /// Returns the aggregate functions of an ORDER BY clause that are not part of
+    /// the output of `plan`, when that output is computed by an aggregation
+    fn missing_order_by_aggregates(
+        &self,
+        plan: &LogicalPlan,
+        order_by: &[OrderByExpr],
+    ) -> Result<Vec<Expr>> {
+        let aggr_input_schema = match aggregate_input_schema(plan) {
+            Some(schema) => schema,
+            None => return Ok(vec![]),
+        };
+        let mut missing = vec![];
+        let mut missing_names = vec![];
+        for e in order_by {
+            let expr = match self.sql_to_rex(&e.expr, &aggr_input_schema) {
+                Ok(expr) => expr,
+                // expressions over the output of `plan`, such as its aliases, are
+                // resolved when sorting
+                Err(_) if self.sql_to_rex(&e.expr, &plan.schema()).is_ok() => continue,
+                Err(err) => return Err(err),
+            };
+            // the aggregates may be nested in the expression, as in `COUNT(*) + 1`
+            for aggr in find_aggregate_exprs(&expr) {
+                let name = aggr.name(&aggr_input_schema)?;
+                if plan.schema().field_with_name(&name).is_err()
+                    && !missing_names.contains(&name)
+                {
+                    missing.push(aggr);
+                    missing_names.push(name);
+                }
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Generate a relational expression for an ORDER BY expression. Output aliases
+    /// of the select list are resolved first; expressions computed by an
+    /// aggregation of `plan`, such as `SUM(salary)`, are resolved to the
+    /// corresponding aggregate output column.
+    fn order_by_to_rex(&self, sql: &SQLExpr, plan: &LogicalPlan) -> Result<Expr> {
+        if let SQLExpr::Identifier(id) = sql {
+            if output_aliases(plan).contains(&id.value) {
+                return Ok(Expr::Column(id.value.clone()));
+            }
+        }
+        if let Some(aggr_input_schema) = aggregate_input_schema(plan) {
+            if let Ok(expr) = self.sql_to_rex(sql, &aggr_input_schema) {
+                let name = expr.name(&aggr_input_schema)?;
+                if plan.schema().field_with_name(&name).is_ok() {
+                    return Ok(Expr::Column(name));
+                }
+                if contains_aggregate_expr(&expr) {
+                    // expressions over aggregates are computed from the output
+                    let outputs = aggregate_outputs(plan)?
+                        .into_iter()
+                        .filter(|(_, name)| plan.schema().field_with_name(name).is_ok())
+                        .collect::<Vec<_>>();
+                    return rebase_aggregate_expr(&expr, &outputs, "ORDER BY");
+                }
+            }
+        }
+        self.sql_to_rex(sql, &plan.schema())
+    }
+
     /// Generate a relational expression from a select SQL expression
-    fn sql_select_to_rex(&self, sql: &SelectItem, schema: &Schema) -> Result<Expr> {
+    fn sql_select_to_rex(&self, sql: &SelectItem, ctx: &PlannerContext) -> Result<Expr> {
         match sql {
-            SelectItem::UnnamedExpr(expr) => self.sql_to_rex(expr, schema),
+            SelectItem::UnnamedExpr(expr) => self.clause_expr_to_rex(expr, ctx),
             SelectItem::ExprWithAlias { expr, alias } => Ok(Alias(
-                Box::new(self.sql_to_rex(&expr, schema)?),
+                Box::new(self.clause_expr_to_rex(&expr, ctx)?),
                 alias.value.clone(),
             )),
             SelectItem::Wildcard => Ok(Expr::Wildcard),
@@ -402,27 +1628,194 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Generate the arguments of a built-in aggregate function
+    fn aggregate_args_to_rex(
+        &self,
+        fun: &aggregates::AggregateFunction,
+        args: &[SQLExpr],
+        ctx: &PlannerContext,
+    ) -> Result<Vec<Expr>> {
+        let args = if *fun == aggregates::AggregateFunction::Count {
+            args.iter()
+                .map(|a| match a {
+                    SQLExpr::Value(Value::Number(_)) => Ok(lit(1_u8)),
+                    SQLExpr::Wildcard => Ok(lit(1_u8)),
+                    _ => self.sql_expr_to_rex(a, ctx),
+                })
+                .collect::<Result<Vec<Expr>>>()?
+        } else {
+            args.iter()
+                .map(|a| self.sql_expr_to_rex(a, ctx))
+                .collect::<Result<Vec<Expr>>>()?
+        };
+        if *fun == aggregates::AggregateFunction::ApproxPercentileCont {
+            validate_percentile(fun, &args)?;
+        }
+        validate_aggregate_args(args)
+    }
+
+    /// Rewrite a comparison of an expression with the NULL literal to
+    /// `IS NULL` / `IS NOT NULL`, returning `None` for any other expression
+    fn null_comparison_to_rex(
+        &self,
+        left: &SQLExpr,
+        op: &BinaryOperator,
+        right: &SQLExpr,
+        ctx: &PlannerContext,
+    ) -> Result<Option<Expr>> {
+        let operand = match (left, right) {
+            (_, SQLExpr::Value(Value::Null)) => left,
+            (SQLExpr::Value(Value::Null), _) => right,
+            _ => return Ok(None),
+        };
+        let operand = Box::new(self.sql_expr_to_rex(operand, ctx)?);
+        let expr = match op {
+            BinaryOperator::Eq => Expr::IsNull(operand),
+            BinaryOperator::NotEq => Expr::IsNotNull(operand),
+            _ => return Ok(None),
+        };
+        self.warnings.borrow_mut().push(format!(
+            "Comparison '{} {} {}' was rewritten to '{:?}'",
+            left, op, right, expr
+        ));
+        Ok(Some(expr))
+    }
+
+    /// Expand an equality or inequality between row constructors, such as
+    /// `ROW(a, b) = ROW(1, 2)`, into the comparisons of their elements: their
+    /// conjunction for `=` and their disjunction for `<>`. Returns `None` when
+    /// neither operand is a row constructor.
+    fn row_comparison_to_rex(
+        &self,
+        left: &SQLExpr,
+        op: &BinaryOperator,
+        right: &SQLExpr,
+        ctx: &PlannerContext,
+    ) -> Result<Option<Expr>> {
+        let (left_elements, right_elements) =
+            match (row_elements(left), row_elements(right)) {
+                (None, None) => return Ok(None),
+                (Some(left), Some(right)) => (left, right),
+                _ => {
+                    return Err(DataFusionError::Plan(format!(
+                        "Cannot compare a row with a scalar in '{} {} {}'",
+                        left, op, right
+                    )))
+                }
+            };
+        if left_elements.len() != right_elements.len() {
+            return Err(DataFusionError::Plan(format!(
+                "Cannot compare rows of {} and {} elements in '{} {} {}'",
+                left_elements.len(),
+                right_elements.len(),
+                left,
+                op,
+                right
+            )));
+        }
+        let comparisons = left_elements
+            .iter()
+            .zip(right_elements.iter())
+            .map(|(l, r)| {
+                let l = self.sql_expr_to_rex(l, ctx)?;
+                let r = self.sql_expr_to_rex(r, ctx)?;
+                match op {
+                    BinaryOperator::Eq => Ok(l.eq(r)),
+                    _ => Ok(l.not_eq(r)),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // a row has at least one element
+        let expr = comparisons
+            .into_iter()
+            .fold(None, |acc: Option<Expr>, e| match acc {
+                None => Some(e),
+                Some(acc) => match op {
+                    BinaryOperator::Eq => Some(acc.and(e)),
+                    _ => Some(acc.or(e)),
+                },
+            })
+            .ok_or_else(|| {
+                DataFusionError::Plan("A row constructor has no elements".to_string())
+            })?;
+        Ok(Some(expr))
+    }
+
+    /// Resolve a `@name` or `@@name` variable reference against the schema provider
+    fn variable_to_rex(&self, var_names: Vec<String>) -> Result<Expr> {
+        match self.schema_provider.get_variable_meta(&var_names) {
+            Some(data_type) => Ok(Expr::ScalarVariable(data_type, var_names)),
+            None => Err(DataFusionError::Plan(format!(
+                "Unknown variable '{}'",
+                var_names.join(".")
+            ))),
+        }
+    }
+
     /// Generate a relational expression from a SQL expression
     pub fn sql_to_rex(&self, sql: &SQLExpr, schema: &Schema) -> Result<Expr> {
+        self.clause_expr_to_rex(sql, &PlannerContext::new(schema))
+    }
+
+    /// Generate a relational expression from an expression of a clause, such as a
+    /// select item or a predicate. Its planning errors name the expression, to
+    /// locate them in long queries. An error within a subquery names the innermost
+    /// expression only, and an expression that is itself an identifier is not
+    /// repeated.
+    fn clause_expr_to_rex(&self, sql: &SQLExpr, ctx: &PlannerContext) -> Result<Expr> {
+        self.error_has_expr_context.set(false);
+        self.sql_expr_to_rex(sql, ctx).map_err(|e| match (e, sql) {
+            (e, SQLExpr::Identifier(_)) | (e, SQLExpr::CompoundIdentifier(_)) => e,
+            (DataFusionError::Plan(msg), _) if !self.error_has_expr_context.get() => {
+                self.error_has_expr_context.set(true);
+                DataFusionError::Plan(format!("{} (in expression '{}')", msg, sql))
+            }
+            (e, _) => e,
+        })
+    }
+
+    /// Generate a relational expression from a SQL expression in the scope of `ctx`
+    fn sql_expr_to_rex(&self, sql: &SQLExpr, ctx: &PlannerContext) -> Result<Expr> {
         match sql {
             SQLExpr::Value(Value::Number(n)) => match n.parse::<i64>() {
                 Ok(n) => Ok(lit(n)),
                 Err(_) => Ok(lit(n.parse::<f64>().unwrap())),
             },
             SQLExpr::Value(Value::SingleQuotedString(ref s)) => Ok(lit(s.clone())),
+            SQLExpr::Value(Value::NationalStringLiteral(ref s)) => Ok(lit(s.clone())),
+            SQLExpr::Value(Value::HexStringLiteral(ref s)) => Ok(Expr::Literal(
+                ScalarValue::Binary(Some(decode_hex_literal(s)?)),
+            )),
+            SQLExpr::Value(Value::Null) => Ok(Expr::Literal(ScalarValue::Utf8(None))),
+            SQLExpr::Value(Value::Date(ref s)) => {
+                typed_string_to_rex(s, &SQLDataType::Date)
+            }
+            SQLExpr::Value(Value::Time(ref s)) => {
+                typed_string_to_rex(s, &SQLDataType::Time)
+            }
+            SQLExpr::Value(Value::Timestamp(ref s)) => {
+                typed_string_to_rex(s, &SQLDataType::Timestamp)
+            }
 
             SQLExpr::Identifier(ref id) => {
                 if &id.value[0..1] == "@" {
-                    let var_names = vec![id.value.clone()];
-                    Ok(Expr::ScalarVariable(var_names))
+                    self.variable_to_rex(vec![id.value.clone()])
                 } else {
-                    match schema.field_with_name(&id.value) {
-                        Ok(field) => Ok(Expr::Column(field.name().clone())),
-                        Err(_) => Err(DataFusionError::Plan(format!(
-                            "Invalid identifier '{}' for schema {}",
-                            id,
-                            schema.to_string()
-                        ))),
+                    let case_insensitive = self.case_insensitive_identifiers;
+                    match ctx.field_with_ident(id, case_insensitive) {
+                        Some(field) => Ok(Expr::Column(field.name().clone())),
+                        None => match ctx.outer_field_with_ident(
+                            std::slice::from_ref(id),
+                            case_insensitive,
+                        ) {
+                            Some(field) => Ok(outer_column(field)),
+                            None => Err(DataFusionError::Plan(format!(
+                                "Invalid identifier '{}' for schema {}{}",
+                                id,
+                                ctx.schema.to_string(),
+                                suggest_field_names(&id.value, ctx.schema)
+                            ))),
+                        },
                     }
                 }
             }
@@ -434,12 +1827,20 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                     var_names.push(id.value);
                 }
                 if &var_names[0][0..1] == "@" {
-                    Ok(Expr::ScalarVariable(var_names))
+                    self.variable_to_rex(var_names)
+                } else if let Some(field) =
+                    ctx.field_with_compound_ident(ids, self.case_insensitive_identifiers)
+                {
+                    Ok(Expr::Column(field.name().clone()))
+                } else if let Some(field) =
+                    ctx.outer_field_with_ident(ids, self.case_insensitive_identifiers)
+                {
+                    Ok(outer_column(field))
                 } else {
                     Err(DataFusionError::Plan(format!(
                         "Invalid compound identifier '{:?}' for schema {}",
                         var_names,
-                        schema.to_string()
+                        ctx.schema.to_string()
                     )))
                 }
             }
@@ -449,22 +1850,80 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
             SQLExpr::Cast {
                 ref expr,
                 ref data_type,
-            } => Ok(Expr::Cast {
-                expr: Box::new(self.sql_to_rex(&expr, schema)?),
-                data_type: convert_data_type(data_type)?,
-            }),
+            } => {
+                let expr = self.sql_expr_to_rex(&expr, ctx)?;
+                let data_type = convert_data_type(data_type)?;
+                // a NULL can be cast to any type; expressions whose type cannot
+                // be resolved here are validated when the physical plan is created
+                let is_null = match &expr {
+                    Expr::Literal(value) => value.is_null(),
+                    _ => false,
+                };
+                if !is_null {
+                    if let Ok(expr_type) = expr.get_type(ctx.schema) {
+                        if !can_cast_types(&expr_type, &data_type) {
+                            return Err(DataFusionError::Plan(format!(
+                                "Cannot cast {:?} to {:?}",
+                                expr_type, data_type
+                            )));
+                        }
+                    }
+                }
+                Ok(Expr::Cast {
+                    expr: Box::new(expr),
+                    data_type,
+                })
+            }
 
             SQLExpr::IsNull(ref expr) => {
-                Ok(Expr::IsNull(Box::new(self.sql_to_rex(expr, schema)?)))
+                Ok(Expr::IsNull(Box::new(self.sql_expr_to_rex(expr, ctx)?)))
             }
 
             SQLExpr::IsNotNull(ref expr) => {
-                Ok(Expr::IsNotNull(Box::new(self.sql_to_rex(expr, schema)?)))
+                Ok(Expr::IsNotNull(Box::new(self.sql_expr_to_rex(expr, ctx)?)))
+            }
+
+            SQLExpr::Exists(ref subquery) => {
+                Ok(Expr::Exists {
+                    subquery: Arc::new(self.query_to_plan_with_outer_scopes(
+                        subquery,
+                        &ctx.subquery_scopes(),
+                    )?),
+                    negated: false,
+                })
+            }
+
+            SQLExpr::InSubquery {
+                ref expr,
+                ref subquery,
+                negated,
+            } => {
+                let subquery = self
+                    .query_to_plan_with_outer_scopes(subquery, &ctx.subquery_scopes())?;
+                let columns = subquery.schema().fields().len();
+                if columns != 1 {
+                    return Err(DataFusionError::Plan(format!(
+                        "IN subquery must return exactly one column, found {}",
+                        columns
+                    )));
+                }
+                Ok(Expr::InSubquery {
+                    expr: Box::new(self.sql_expr_to_rex(expr, ctx)?),
+                    subquery: Arc::new(subquery),
+                    negated: *negated,
+                })
             }
 
-            SQLExpr::UnaryOp { ref op, ref expr } => match *op {
-                UnaryOperator::Not => {
-                    Ok(Expr::Not(Box::new(self.sql_to_rex(expr, schema)?)))
+            SQLExpr::UnaryOp { ref op, ref expr } => match (op, expr.as_ref()) {
+                (UnaryOperator::Not, SQLExpr::Exists(subquery)) => Ok(Expr::Exists {
+                    subquery: Arc::new(self.query_to_plan_with_outer_scopes(
+                        subquery,
+                        &ctx.subquery_scopes(),
+                    )?),
+                    negated: true,
+                }),
+                (UnaryOperator::Not, _) => {
+                    Ok(Expr::Not(Box::new(self.sql_expr_to_rex(expr, ctx)?)))
                 }
                 _ => Err(DataFusionError::Internal(format!(
                     "SQL binary operator cannot be interpreted as a unary operator"
@@ -476,6 +1935,21 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                 ref op,
                 ref right,
             } => {
+                if self.rewrite_null_comparisons {
+                    if let Some(expr) =
+                        self.null_comparison_to_rex(left, op, right, ctx)?
+                    {
+                        return Ok(expr);
+                    }
+                }
+                if let BinaryOperator::Eq | BinaryOperator::NotEq = op {
+                    if let Some(expr) =
+                        self.row_comparison_to_rex(left, op, right, ctx)?
+                    {
+                        return Ok(expr);
+                    }
+                }
+
                 let operator = match *op {
                     BinaryOperator::Gt => Ok(Operator::Gt),
                     BinaryOperator::GtEq => Ok(Operator::GtEq),
@@ -492,28 +1966,144 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                     BinaryOperator::Or => Ok(Operator::Or),
                     BinaryOperator::Like => Ok(Operator::Like),
                     BinaryOperator::NotLike => Ok(Operator::NotLike),
+                    // `^` is exponentiation, as in PostgreSQL, rather than the
+                    // bitwise XOR of other dialects
+                    BinaryOperator::BitwiseXor => {
+                        return Ok(Expr::ScalarFunction {
+                            fun: functions::BuiltinScalarFunction::Power,
+                            args: vec![
+                                self.sql_expr_to_rex(&left, ctx)?,
+                                self.sql_expr_to_rex(&right, ctx)?,
+                            ],
+                        });
+                    }
                     _ => Err(DataFusionError::NotImplemented(format!(
                         "Unsupported SQL binary operator {:?}",
                         op
                     ))),
                 }?;
 
+                let left = self.sql_expr_to_rex(&left, ctx)?;
+                let right = self.sql_expr_to_rex(&right, ctx)?;
+                let (left, right) = match operator {
+                    Operator::Plus
+                    | Operator::Minus
+                    | Operator::Multiply
+                    | Operator::Divide
+                    | Operator::Modulus => {
+                        // operands whose type cannot be resolved here are
+                        // coerced when the physical plan is created
+                        let schema = ctx.schema;
+                        match (left.get_type(schema), right.get_type(schema)) {
+                            (Ok(left_type), Ok(right_type)) => (
+                                cast_to_common_numeric_type(
+                                    left,
+                                    &left_type,
+                                    &right_type,
+                                ),
+                                cast_to_common_numeric_type(
+                                    right,
+                                    &right_type,
+                                    &left_type,
+                                ),
+                            ),
+                            _ => (left, right),
+                        }
+                    }
+                    _ => (left, right),
+                };
+
                 Ok(Expr::BinaryExpr {
-                    left: Box::new(self.sql_to_rex(&left, &schema)?),
+                    left: Box::new(left),
                     op: operator,
-                    right: Box::new(self.sql_to_rex(&right, &schema)?),
+                    right: Box::new(right),
                 })
             }
 
+            SQLExpr::Function(_) if row_elements(sql).is_some() => {
+                Err(DataFusionError::NotImplemented(
+                    "ROW constructors are only supported in = and <> comparisons"
+                        .to_string(),
+                ))
+            }
+
             SQLExpr::Function(function) => {
                 let name: String = function.name.to_string();
 
-                // first, scalar built-in
+                // first, window functions
+                if let Some(window) = &function.over {
+                    let fun = window_functions::WindowFunction::from_str(&name)?;
+                    let args = match &fun {
+                        window_functions::WindowFunction::AggregateFunction(fun) => {
+                            self.aggregate_args_to_rex(fun, &function.args, ctx)?
+                        }
+                        _ => function
+                            .args
+                            .iter()
+                            .map(|a| self.sql_expr_to_rex(a, ctx))
+                            .collect::<Result<Vec<Expr>>>()?,
+                    };
+                    let partition_by = window
+                        .partition_by
+                        .iter()
+                        .map(|e| self.sql_expr_to_rex(e, ctx))
+                        .collect::<Result<Vec<Expr>>>()?;
+                    let order_by = window
+                        .order_by
+                        .iter()
+                        .map(|e| {
+                            Ok(Expr::Sort {
+                                expr: Box::new(self.sql_expr_to_rex(&e.expr, ctx)?),
+                                // same defaults as the ORDER BY of a query
+                                asc: e.asc.unwrap_or(true),
+                                nulls_first: e
+                                    .nulls_first
+                                    .unwrap_or(self.default_nulls_first),
+                            })
+                        })
+                        .collect::<Result<Vec<Expr>>>()?;
+                    validate_window_function(&fun, &args, &order_by)?;
+
+                    return Ok(Expr::WindowFunction {
+                        fun,
+                        args,
+                        partition_by,
+                        order_by,
+                        window_frame: window.window_frame.clone(),
+                    });
+                }
+
+                // built-in window functions are only valid with an OVER clause
+                if let Ok(fun) = window_functions::BuiltInWindowFunction::from_str(&name)
+                {
+                    return Err(DataFusionError::Plan(format!(
+                        "The window function {} requires an OVER clause",
+                        fun
+                    )));
+                }
+
+                // `MOD(a, b)` is the function form of `a % b`
+                if name.to_uppercase() == "MOD" {
+                    if function.args.len() != 2 {
+                        return Err(DataFusionError::Plan(format!(
+                            "The function MOD expects 2 arguments, found {}",
+                            function.args.len()
+                        )));
+                    }
+                    let modulus = SQLExpr::BinaryOp {
+                        left: Box::new(function.args[0].clone()),
+                        op: BinaryOperator::Modulus,
+                        right: Box::new(function.args[1].clone()),
+                    };
+                    return self.sql_expr_to_rex(&modulus, ctx);
+                }
+
+                // next, scalar built-in
                 if let Ok(fun) = functions::BuiltinScalarFunction::from_str(&name) {
                     let args = function
                         .args
                         .iter()
-                        .map(|a| self.sql_to_rex(a, schema))
+                        .map(|a| self.sql_expr_to_rex(a, ctx))
                         .collect::<Result<Vec<Expr>>>()?;
 
                     return Ok(Expr::ScalarFunction { fun, args });
@@ -521,28 +2111,13 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
 
                 // next, aggregate built-ins
                 if let Ok(fun) = aggregates::AggregateFunction::from_str(&name) {
-                    let args = if fun == aggregates::AggregateFunction::Count {
-                        function
-                            .args
-                            .iter()
-                            .map(|a| match a {
-                                SQLExpr::Value(Value::Number(_)) => Ok(lit(1_u8)),
-                                SQLExpr::Wildcard => Ok(lit(1_u8)),
-                                _ => self.sql_to_rex(a, schema),
-                            })
-                            .collect::<Result<Vec<Expr>>>()?
-                    } else {
-                        function
-                            .args
-                            .iter()
-                            .map(|a| self.sql_to_rex(a, schema))
-                            .collect::<Result<Vec<Expr>>>()?
-                    };
+                    let args = self.aggregate_args_to_rex(&fun, &function.args, ctx)?;
 
                     return Ok(Expr::AggregateFunction {
                         fun,
                         distinct: function.distinct,
                         args,
+                        filter: None,
                     });
                 };
 
@@ -552,7 +2127,7 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                         let args = function
                             .args
                             .iter()
-                            .map(|a| self.sql_to_rex(a, schema))
+                            .map(|a| self.sql_expr_to_rex(a, ctx))
                             .collect::<Result<Vec<Expr>>>()?;
 
                         Ok(Expr::ScalarUDF {
@@ -565,8 +2140,9 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                             let args = function
                                 .args
                                 .iter()
-                                .map(|a| self.sql_to_rex(a, schema))
+                                .map(|a| self.sql_expr_to_rex(a, ctx))
                                 .collect::<Result<Vec<Expr>>>()?;
+                            let args = validate_aggregate_args(args)?;
 
                             Ok(Expr::AggregateUDF {
                                 fun: fm.clone(),
@@ -581,7 +2157,7 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
                 }
             }
 
-            SQLExpr::Nested(e) => self.sql_to_rex(&e, &schema),
+            SQLExpr::Nested(e) => self.sql_expr_to_rex(&e, ctx),
 
             _ => Err(DataFusionError::NotImplemented(format!(
                 "Unsupported ast node {:?} in sqltorel",
@@ -591,292 +2167,2943 @@ impl<'a, S: SchemaProvider> SqlToRel<'a, S> {
     }
 }
 
-/// Determine if an expression is an aggregate expression or not
-fn is_aggregate_expr(e: &Expr) -> bool {
-    match e {
-        Expr::AggregateFunction { .. } | Expr::AggregateUDF { .. } => true,
-        Expr::Alias(expr, _) => is_aggregate_expr(expr),
-        _ => false,
+/// The query-level settings that are captured by `SqlToRel::settings_to_plan`
+const QUERY_SETTINGS: &[&str] = &["max_rows", "timeout"];
+
+/// Collects the base tables referenced by `query` into `tables`, where `ctes` are the
+/// names of the common table expressions visible from `query`
+fn collect_query_tables(query: &Query, ctes: &[String], tables: &mut Vec<String>) {
+    let mut ctes = ctes.to_vec();
+    for cte in &query.ctes {
+        collect_query_tables(&cte.query, &ctes, tables);
+        ctes.push(cte.alias.name.value.clone());
     }
+    collect_set_expr_tables(&query.body, &ctes, tables);
 }
 
-/// Convert SQL data type to relational representation of data type
-pub fn convert_data_type(sql: &SQLDataType) -> Result<DataType> {
-    match sql {
-        SQLDataType::Boolean => Ok(DataType::Boolean),
-        SQLDataType::SmallInt => Ok(DataType::Int16),
-        SQLDataType::Int => Ok(DataType::Int32),
-        SQLDataType::BigInt => Ok(DataType::Int64),
-        SQLDataType::Float(_) | SQLDataType::Real => Ok(DataType::Float64),
-        SQLDataType::Double => Ok(DataType::Float64),
-        SQLDataType::Char(_) | SQLDataType::Varchar(_) => Ok(DataType::Utf8),
-        SQLDataType::Timestamp => Ok(DataType::Timestamp(TimeUnit::Nanosecond, None)),
-        other => Err(DataFusionError::NotImplemented(format!(
-            "Unsupported SQL type {:?}",
-            other
-        ))),
+fn collect_set_expr_tables(
+    set_expr: &SetExpr,
+    ctes: &[String],
+    tables: &mut Vec<String>,
+) {
+    match set_expr {
+        SetExpr::Select(select) => {
+            for table_with_joins in &select.from {
+                collect_table_with_joins_tables(table_with_joins, ctes, tables);
+            }
+            for item in &select.projection {
+                match item {
+                    SelectItem::UnnamedExpr(expr)
+                    | SelectItem::ExprWithAlias { expr, .. } => {
+                        collect_expr_tables(expr, ctes, tables)
+                    }
+                    _ => {}
+                }
+            }
+            let exprs = select.selection.iter().chain(select.having.iter());
+            for expr in exprs.chain(select.group_by.iter()) {
+                collect_expr_tables(expr, ctes, tables);
+            }
+        }
+        SetExpr::Query(query) => collect_query_tables(query, ctes, tables),
+        SetExpr::SetOperation { left, right, .. } => {
+            collect_set_expr_tables(left, ctes, tables);
+            collect_set_expr_tables(right, ctes, tables);
+        }
+        _ => {}
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{logical_plan::create_udf, sql::parser::DFParser};
-    use functions::ScalarFunctionImplementation;
-
-    #[test]
-    fn select_no_relation() {
-        quick_test(
-            "SELECT 1",
-            "Projection: Int64(1)\
-             \n  EmptyRelation",
-        );
+fn collect_table_with_joins_tables(
+    table_with_joins: &TableWithJoins,
+    ctes: &[String],
+    tables: &mut Vec<String>,
+) {
+    collect_table_factor_tables(&table_with_joins.relation, ctes, tables);
+    for join in &table_with_joins.joins {
+        collect_table_factor_tables(&join.relation, ctes, tables);
+        match &join.join_operator {
+            JoinOperator::Inner(JoinConstraint::On(expr))
+            | JoinOperator::LeftOuter(JoinConstraint::On(expr))
+            | JoinOperator::RightOuter(JoinConstraint::On(expr))
+            | JoinOperator::FullOuter(JoinConstraint::On(expr)) => {
+                collect_expr_tables(expr, ctes, tables)
+            }
+            _ => {}
+        }
     }
+}
 
-    #[test]
-    fn select_scalar_func_with_literal_no_relation() {
-        quick_test(
-            "SELECT sqrt(9)",
-            "Projection: sqrt(Int64(9))\
-             \n  EmptyRelation",
-        );
+fn collect_table_factor_tables(
+    relation: &TableFactor,
+    ctes: &[String],
+    tables: &mut Vec<String>,
+) {
+    match relation {
+        TableFactor::Table { name, .. } => {
+            let name = name.to_string();
+            if !ctes.contains(&name) && !tables.contains(&name) {
+                tables.push(name);
+            }
+        }
+        TableFactor::Derived { subquery, .. } => {
+            collect_query_tables(subquery, ctes, tables)
+        }
+        TableFactor::NestedJoin(table_with_joins) => {
+            collect_table_with_joins_tables(table_with_joins, ctes, tables)
+        }
     }
+}
 
-    #[test]
-    fn select_simple_filter() {
-        let sql = "SELECT id, first_name, last_name \
-                   FROM person WHERE state = 'CO'";
-        let expected = "Projection: #id, #first_name, #last_name\
-                        \n  Filter: #state Eq Utf8(\"CO\")\
-                        \n    TableScan: person projection=None";
-        quick_test(sql, expected);
+fn collect_expr_tables(expr: &SQLExpr, ctes: &[String], tables: &mut Vec<String>) {
+    match expr {
+        SQLExpr::Subquery(query) | SQLExpr::Exists(query) => {
+            collect_query_tables(query, ctes, tables)
+        }
+        SQLExpr::InSubquery { expr, subquery, .. } => {
+            collect_expr_tables(expr, ctes, tables);
+            collect_query_tables(subquery, ctes, tables);
+        }
+        SQLExpr::BinaryOp { left, right, .. } => {
+            collect_expr_tables(left, ctes, tables);
+            collect_expr_tables(right, ctes, tables);
+        }
+        SQLExpr::UnaryOp { expr, .. }
+        | SQLExpr::Nested(expr)
+        | SQLExpr::IsNull(expr)
+        | SQLExpr::IsNotNull(expr)
+        | SQLExpr::Cast { expr, .. } => collect_expr_tables(expr, ctes, tables),
+        SQLExpr::Between {
+            expr, low, high, ..
+        } => {
+            collect_expr_tables(expr, ctes, tables);
+            collect_expr_tables(low, ctes, tables);
+            collect_expr_tables(high, ctes, tables);
+        }
+        SQLExpr::InList { expr, list, .. } => {
+            collect_expr_tables(expr, ctes, tables);
+            for e in list {
+                collect_expr_tables(e, ctes, tables);
+            }
+        }
+        SQLExpr::Function(function) => {
+            for arg in &function.args {
+                collect_expr_tables(arg, ctes, tables);
+            }
+        }
+        _ => {}
     }
+}
 
-    #[test]
-    fn select_neg_filter() {
-        let sql = "SELECT id, first_name, last_name \
-                   FROM person WHERE NOT state";
-        let expected = "Projection: #id, #first_name, #last_name\
+/// The key of the schema metadata entry holding the length `n` of the column
+/// `column` declared as `CHAR(n)`. Arrow fields have no metadata of their own.
+pub fn char_length_key(column: &str) -> String {
+    format!("{}.char_length", column)
+}
+
+/// A correlated reference to the column `field` of an enclosing query
+fn outer_column(field: &Field) -> Expr {
+    Expr::OuterColumn(field.name().clone(), field.data_type().clone())
+}
+
+/// Returns a "did you mean" hint listing the fields of `schema` whose names are the
+/// closest to `name`, or an empty string when no field name is close enough
+fn suggest_field_names(name: &str, schema: &Schema) -> String {
+    suggest_names(name, schema.fields().iter().map(|f| f.name().as_str()))
+}
+
+/// Returns a "did you mean" hint listing the `candidates` that are the closest to
+/// `name`, or an empty string when no candidate is close enough
+fn suggest_names<'b>(name: &str, candidates: impl Iterator<Item = &'b str>) -> String {
+    let max_distance = std::cmp::max(1, name.chars().count() / 3);
+    let distances = candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    match distances.iter().map(|(distance, _)| *distance).min() {
+        Some(min) => {
+            let names = distances
+                .iter()
+                .filter(|(distance, _)| *distance == min)
+                .map(|(_, candidate)| format!("'{}'", candidate))
+                .collect::<Vec<_>>();
+            format!("; did you mean {}?", names.join(" or "))
+        }
+        None => "".to_string(),
+    }
+}
+
+/// The Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Returns the elements of a row constructor `ROW(a, b, ...)`, or `None` if `expr`
+/// is not one
+fn row_elements(expr: &SQLExpr) -> Option<&[SQLExpr]> {
+    match expr {
+        SQLExpr::Nested(expr) => row_elements(expr),
+        SQLExpr::Function(function)
+            if function.over.is_none()
+                && function.name.to_string().eq_ignore_ascii_case("row") =>
+        {
+            Some(&function.args)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the error reported for a statement kind that the planner does not support
+fn unsupported_statement(kind: &str) -> Result<LogicalPlan> {
+    Err(DataFusionError::NotImplemented(format!(
+        "{} is not supported",
+        kind
+    )))
+}
+
+/// Determine if an expression is an aggregate expression or not
+fn is_aggregate_expr(e: &Expr) -> bool {
+    match e {
+        Expr::AggregateFunction { .. } | Expr::AggregateUDF { .. } => true,
+        Expr::Alias(expr, _) => is_aggregate_expr(expr),
+        _ => false,
+    }
+}
+
+/// Whether the GROUP BY clause is `GROUP BY ALL`, which the parser reads as an
+/// unquoted identifier
+fn is_group_by_all(group_by: &[SQLExpr]) -> bool {
+    match group_by {
+        [SQLExpr::Identifier(id)] => {
+            id.quote_style.is_none() && id.value.to_uppercase() == "ALL"
+        }
+        _ => false,
+    }
+}
+
+/// Whether `e` is or contains an aggregate function, not counting the ones of
+/// subqueries
+fn contains_aggregate_expr(e: &Expr) -> bool {
+    is_aggregate_expr(e)
+        || utils::expr_sub_expressions(e)
+            .unwrap_or_default()
+            .into_iter()
+            .any(contains_aggregate_expr)
+}
+
+/// Checks the requirements of the window functions that access other rows of the
+/// window by offset: an ORDER BY in their window, and an offset that is a
+/// non-negative integer literal
+fn validate_window_function(
+    fun: &window_functions::WindowFunction,
+    args: &[Expr],
+    order_by: &[Expr],
+) -> Result<()> {
+    match fun {
+        window_functions::WindowFunction::BuiltInWindowFunction(
+            window_functions::BuiltInWindowFunction::Lag,
+        )
+        | window_functions::WindowFunction::BuiltInWindowFunction(
+            window_functions::BuiltInWindowFunction::Lead,
+        ) => {
+            if order_by.is_empty() {
+                return Err(DataFusionError::Plan(format!(
+                    "The window function {} requires an ORDER BY in its window",
+                    fun
+                )));
+            }
+            match args.get(1) {
+                None => Ok(()),
+                Some(Expr::Literal(ScalarValue::Int64(Some(n)))) if *n >= 0 => Ok(()),
+                Some(offset) => Err(DataFusionError::Plan(format!(
+                    "The offset of {} must be a non-negative integer literal, found {:?}",
+                    fun, offset
+                ))),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the arguments of an aggregate function, or an error when one of them
+/// is itself an aggregate
+fn validate_aggregate_args(args: Vec<Expr>) -> Result<Vec<Expr>> {
+    if args.iter().any(contains_aggregate_expr) {
+        return Err(DataFusionError::Plan(
+            "aggregate function calls cannot be nested".to_string(),
+        ));
+    }
+    Ok(args)
+}
+
+/// Checks that the second argument of the percentile aggregate `fun` is a
+/// float literal between 0 and 1
+fn validate_percentile(fun: &aggregates::AggregateFunction, args: &[Expr]) -> Result<()> {
+    match args.get(1) {
+        Some(Expr::Literal(ScalarValue::Float64(Some(p)))) if (0.0..=1.0).contains(p) => {
+            Ok(())
+        }
+        Some(arg) => Err(DataFusionError::Plan(format!(
+            "The percentile of {} must be a float literal between 0 and 1, found {:?}",
+            fun, arg
+        ))),
+        None => Err(DataFusionError::Plan(format!(
+            "The function {} expects 2 arguments, found {}",
+            fun,
+            args.len()
+        ))),
+    }
+}
+
+/// Collects the window function expressions in `exprs`, without duplicates
+fn find_window_exprs(exprs: &[Expr]) -> Vec<Expr> {
+    let mut window_exprs = vec![];
+    for expr in exprs {
+        collect_window_exprs(expr, &mut window_exprs);
+    }
+    window_exprs
+}
+
+fn collect_window_exprs(expr: &Expr, accum: &mut Vec<Expr>) {
+    match expr {
+        Expr::WindowFunction { .. } => {
+            let name = format!("{:?}", expr);
+            if !accum.iter().any(|e| format!("{:?}", e) == name) {
+                accum.push(expr.clone());
+            }
+        }
+        _ => {
+            // expressions without sub-expressions, such as wildcards, contain no
+            // window functions
+            for e in utils::expr_sub_expressions(expr).unwrap_or_default() {
+                collect_window_exprs(e, accum);
+            }
+        }
+    }
+}
+
+/// Replaces the occurrences of `base_exprs` in `expr` by references to the columns
+/// they are computed into
+fn rebase_expr(expr: &Expr, base_exprs: &[Expr], schema: &Schema) -> Result<Expr> {
+    let name = format!("{:?}", expr);
+    if base_exprs.iter().any(|e| format!("{:?}", e) == name) {
+        return Ok(Expr::Column(expr.name(schema)?));
+    }
+    match expr {
+        Expr::Wildcard => Ok(Expr::Wildcard),
+        _ => {
+            let sub_exprs = utils::expr_sub_expressions(expr)?
+                .iter()
+                .map(|e| rebase_expr(e, base_exprs, schema))
+                .collect::<Result<Vec<_>>>()?;
+            utils::rewrite_expression(expr, &sub_exprs)
+        }
+    }
+}
+
+/// Replaces the references to the aliases of the projection in `expr` by the
+/// aliased expressions
+fn replace_aliases(expr: &Expr, aliases: &[(&String, &Expr)]) -> Result<Expr> {
+    match expr {
+        Expr::Column(name) => {
+            Ok(match aliases.iter().find(|(alias, _)| *alias == name) {
+                Some((_, aliased)) => (*aliased).clone(),
+                None => expr.clone(),
+            })
+        }
+        Expr::Wildcard => Ok(Expr::Wildcard),
+        _ => {
+            let sub_exprs = utils::expr_sub_expressions(expr)?
+                .iter()
+                .map(|e| replace_aliases(e, aliases))
+                .collect::<Result<Vec<_>>>()?;
+            utils::rewrite_expression(expr, &sub_exprs)
+        }
+    }
+}
+
+/// Replaces the grouping expressions and aggregates in `expr` by references to
+/// the columns of the aggregation they are output as. Any other column is
+/// neither grouped nor aggregated, and cannot be referenced by the `clause`.
+fn rebase_aggregate_expr(
+    expr: &Expr,
+    outputs: &[(Expr, String)],
+    clause: &str,
+) -> Result<Expr> {
+    let name = format!("{:?}", expr);
+    if let Some((_, column)) = outputs.iter().find(|(e, _)| format!("{:?}", e) == name) {
+        return Ok(Expr::Column(column.clone()));
+    }
+    match expr {
+        Expr::Column(name) => Err(DataFusionError::Plan(format!(
+            "{} references the column '{}', which is neither grouped nor aggregated",
+            clause, name
+        ))),
+        Expr::Wildcard => Ok(Expr::Wildcard),
+        _ => {
+            let sub_exprs = utils::expr_sub_expressions(expr)?
+                .iter()
+                .map(|e| rebase_aggregate_expr(e, outputs, clause))
+                .collect::<Result<Vec<_>>>()?;
+            utils::rewrite_expression(expr, &sub_exprs)
+        }
+    }
+}
+
+/// Returns `expr` without its alias, if any
+fn unalias(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Alias(expr, _) => unalias(expr),
+        _ => expr,
+    }
+}
+
+/// Collects the aggregate function expressions in `expr`, without duplicates
+fn find_aggregate_exprs(expr: &Expr) -> Vec<Expr> {
+    let mut aggr_exprs = vec![];
+    collect_aggregate_exprs(expr, &mut aggr_exprs);
+    aggr_exprs
+}
+
+fn collect_aggregate_exprs(expr: &Expr, accum: &mut Vec<Expr>) {
+    if is_aggregate_expr(expr) {
+        let name = format!("{:?}", expr);
+        if !accum.iter().any(|e| format!("{:?}", e) == name) {
+            accum.push(expr.clone());
+        }
+    } else {
+        for e in utils::expr_sub_expressions(expr).unwrap_or_default() {
+            collect_aggregate_exprs(e, accum);
+        }
+    }
+}
+
+/// Returns the name of `expr` ignoring any alias
+fn unaliased_name(expr: &Expr, schema: &Schema) -> Result<String> {
+    match expr {
+        Expr::Alias(expr, _) => unaliased_name(expr, schema),
+        _ => expr.name(schema),
+    }
+}
+
+/// Returns the aliases given to the output expressions of `plan`
+fn output_aliases(plan: &LogicalPlan) -> Vec<String> {
+    let exprs = match plan {
+        LogicalPlan::Projection { expr, .. } => expr.clone(),
+        LogicalPlan::Aggregate {
+            group_expr,
+            aggr_expr,
+            ..
+        } => group_expr.iter().chain(aggr_expr.iter()).cloned().collect(),
+        _ => vec![],
+    };
+    exprs
+        .iter()
+        .filter_map(|e| match e {
+            Expr::Alias(_, alias) => Some(alias.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the expression of the projection aliased by the identifier `sql`, unless
+/// it is an aggregate, which cannot be grouped by
+fn aliased_projection_expr(sql: &SQLExpr, projection_expr: &[Expr]) -> Option<Expr> {
+    match sql {
+        SQLExpr::Identifier(id) => projection_expr.iter().find_map(|e| match e {
+            Expr::Alias(expr, alias)
+                if *alias == id.value && !is_aggregate_expr(expr) =>
+            {
+                Some(expr.as_ref().clone())
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the name the columns of a FROM clause made of a single relation can be
+/// qualified with: the alias of the relation, or else the name of its table
+fn single_relation_name(from: &[TableWithJoins]) -> Option<&str> {
+    match from {
+        [TableWithJoins { relation, joins }] if joins.is_empty() => match relation {
+            TableFactor::Table {
+                alias: Some(alias), ..
+            }
+            | TableFactor::Derived {
+                alias: Some(alias), ..
+            } => Some(&alias.name.value),
+            TableFactor::Table { name, .. } => name.0.last().map(|id| id.value.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the aggregation that produces the output of `plan`, below the
+/// projections and the filter of a HAVING clause, if any
+fn output_aggregate(plan: &LogicalPlan) -> Option<&LogicalPlan> {
+    match plan {
+        LogicalPlan::Aggregate { .. } => Some(plan),
+        LogicalPlan::Projection { input, .. } | LogicalPlan::Filter { input, .. } => {
+            output_aggregate(input)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the input schema of the aggregation that produces the output of `plan`,
+/// if any
+fn aggregate_input_schema(plan: &LogicalPlan) -> Option<SchemaRef> {
+    match output_aggregate(plan) {
+        Some(LogicalPlan::Aggregate { input, .. }) => Some(input.schema().clone()),
+        _ => None,
+    }
+}
+
+/// Returns the grouping expressions and aggregates of the aggregation that
+/// produces the output of `plan`, by the name of the column they are output as
+fn aggregate_outputs(plan: &LogicalPlan) -> Result<Vec<(Expr, String)>> {
+    match output_aggregate(plan) {
+        Some(LogicalPlan::Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            ..
+        }) => grouping_set_to_exprlist(group_expr)
+            .iter()
+            .chain(aggr_expr.iter())
+            .map(|e| Ok((unalias(e).clone(), e.name(input.schema())?)))
+            .collect(),
+        _ => Ok(vec![]),
+    }
+}
+
+/// Returns `plan`, whose output is computed by an aggregation, with `extra_aggr_expr`
+/// added to the aggregation and appended to the output. Aggregates that are already
+/// computed, but projected away, are only appended to the output.
+fn add_aggregates(plan: &LogicalPlan, extra_aggr_expr: Vec<Expr>) -> Result<LogicalPlan> {
+    match plan {
+        LogicalPlan::Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            schema,
+        } => {
+            let mut aggr_expr = aggr_expr.clone();
+            for e in extra_aggr_expr {
+                if schema.field_with_name(&e.name(input.schema())?).is_err() {
+                    aggr_expr.push(e);
+                }
+            }
+            LogicalPlanBuilder::from(input.as_ref())
+                .aggregate(group_expr.clone(), aggr_expr)?
+                .build()
+        }
+        LogicalPlan::Filter { input, predicate } => {
+            let input = add_aggregates(input, extra_aggr_expr)?;
+            LogicalPlanBuilder::from(&input)
+                .filter(predicate.clone())?
+                .build()
+        }
+        LogicalPlan::Projection { input, expr, .. } => {
+            let aggr_input_schema = aggregate_input_schema(input).ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "Expected an aggregation, found {:?}",
+                    input
+                ))
+            })?;
+            let columns = extra_aggr_expr
+                .iter()
+                .map(|e| Ok(Expr::Column(e.name(&aggr_input_schema)?)))
+                .collect::<Result<Vec<_>>>()?;
+            let input = add_aggregates(input, extra_aggr_expr)?;
+            let expr = expr.iter().cloned().chain(columns).collect();
+            LogicalPlanBuilder::from(&input).project(expr)?.build()
+        }
+        _ => Err(DataFusionError::Internal(format!(
+            "Expected an aggregation, found {:?}",
+            plan
+        ))),
+    }
+}
+
+/// Decodes the digits of a hex string literal such as `X'1F'` into bytes
+fn decode_hex_literal(s: &str) -> Result<Vec<u8>> {
+    let invalid =
+        || DataFusionError::Plan(format!("Invalid hex string literal X'{}'", s));
+    if s.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+/// Evaluates an integer expression made of literals and arithmetic operators,
+/// returning `None` for any other expression or on overflow
+fn eval_integer_expr(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(ScalarValue::Int64(Some(n))) => Some(*n),
+        Expr::BinaryExpr { left, op, right } => {
+            let left = eval_integer_expr(left)?;
+            let right = eval_integer_expr(right)?;
+            match op {
+                Operator::Plus => left.checked_add(right),
+                Operator::Minus => left.checked_sub(right),
+                Operator::Multiply => left.checked_mul(right),
+                Operator::Divide => left.checked_div(right),
+                Operator::Modulus => left.checked_rem(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Casts `expr`, an operand of an arithmetic expression, to the numeric type both
+/// operands are coerced to, such as `Int64` for `Int32` and `Int64` operands, or
+/// `Float64` for `Int32` and `Float64` operands
+fn cast_to_common_numeric_type(
+    expr: Expr,
+    expr_type: &DataType,
+    other_type: &DataType,
+) -> Expr {
+    match numerical_coercion(expr_type, other_type) {
+        Some(common_type) if common_type != *expr_type => Expr::Cast {
+            expr: Box::new(expr),
+            data_type: common_type,
+        },
+        _ => expr,
+    }
+}
+
+/// Appends the operands of the top-level `AND`s of `expr` to `conjuncts`
+fn split_conjunction<'a>(expr: &'a SQLExpr, conjuncts: &mut Vec<&'a SQLExpr>) {
+    match expr {
+        SQLExpr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            split_conjunction(left, conjuncts);
+            split_conjunction(right, conjuncts);
+        }
+        SQLExpr::Nested(expr) => split_conjunction(expr, conjuncts),
+        other => conjuncts.push(other),
+    }
+}
+
+/// Plan a string literal prefixed with its type, such as `TIMESTAMP '...'`.
+/// The string is parsed here and its integer representation is cast to the
+/// temporal type, the same way `now()` produces its timestamp.
+fn typed_string_to_rex(value: &str, sql_type: &SQLDataType) -> Result<Expr> {
+    let data_type = convert_data_type(sql_type)?;
+    let parse_error = || {
+        DataFusionError::Plan(format!(
+            "Cannot parse '{}' as a value of type {:?}",
+            value, data_type
+        ))
+    };
+    let expr = match data_type {
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => {
+            lit(string_to_timestamp_nanos(value).map_err(|_| parse_error())?)
+        }
+        DataType::Date32(DateUnit::Day) => {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| parse_error())?;
+            let days = date.signed_duration_since(NaiveDate::from_ymd(1970, 1, 1));
+            lit(days.num_days() as i32)
+        }
+        DataType::Time64(TimeUnit::Nanosecond) => {
+            let time = NaiveTime::parse_from_str(value, "%H:%M:%S%.f")
+                .map_err(|_| parse_error())?;
+            lit(time.num_seconds_from_midnight() as i64 * 1_000_000_000
+                + time.nanosecond() as i64)
+        }
+        _ => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Typed string literals of type {:?} are not supported",
+                data_type
+            )))
+        }
+    };
+    Ok(Expr::Cast {
+        expr: Box::new(expr),
+        data_type,
+    })
+}
+
+/// Convert SQL data type to relational representation of data type
+pub fn convert_data_type(sql: &SQLDataType) -> Result<DataType> {
+    match sql {
+        SQLDataType::Boolean => Ok(DataType::Boolean),
+        SQLDataType::SmallInt => Ok(DataType::Int16),
+        SQLDataType::Int => Ok(DataType::Int32),
+        SQLDataType::BigInt => Ok(DataType::Int64),
+        SQLDataType::Float(_) | SQLDataType::Real => Ok(DataType::Float64),
+        SQLDataType::Double => Ok(DataType::Float64),
+        SQLDataType::Char(_) | SQLDataType::Varchar(_) => Ok(DataType::Utf8),
+        SQLDataType::Date => Ok(DataType::Date32(DateUnit::Day)),
+        SQLDataType::Time => Ok(DataType::Time64(TimeUnit::Nanosecond)),
+        SQLDataType::Timestamp => Ok(DataType::Timestamp(TimeUnit::Nanosecond, None)),
+        other => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported SQL type {:?}",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{logical_plan::create_udf, sql::parser::DFParser};
+    use functions::ScalarFunctionImplementation;
+    use std::collections::HashSet;
+
+    #[test]
+    fn select_no_relation() {
+        quick_test(
+            "SELECT 1",
+            "Projection: Int64(1)\
+             \n  EmptyRelation",
+        );
+    }
+
+    #[test]
+    fn select_aliased_columns_no_relation() -> Result<()> {
+        let sql = "SELECT 1 AS a, 'x' AS b, sqrt(9) AS c";
+        quick_test(
+            sql,
+            "Projection: Int64(1) AS a, Utf8(\"x\") AS b, sqrt(Int64(9)) AS c\
+             \n  EmptyRelation",
+        );
+
+        // the output fields are named by the aliases
+        let plan = logical_plan(sql)?;
+        let names: Vec<&str> = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect();
+        assert_eq!(vec!["a", "b", "c"], names);
+
+        let sql = "SELECT 1 AS a, 'x' AS a";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).starts_with(
+            "Plan(\"Projections require unique expression names \
+             but the expression \\\"Int64(1) AS a\\\" at position 0"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn select_scalar_func_with_literal_no_relation() {
+        quick_test(
+            "SELECT sqrt(9)",
+            "Projection: sqrt(Int64(9))\
+             \n  EmptyRelation",
+        );
+    }
+
+    #[test]
+    fn select_simple_filter() {
+        let sql = "SELECT id, first_name, last_name \
+                   FROM person WHERE state = 'CO'";
+        let expected = "Projection: #id, #first_name, #last_name\
+                        \n  Filter: #state Eq Utf8(\"CO\")\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_neg_filter() {
+        let sql = "SELECT id, first_name, last_name \
+                   FROM person WHERE NOT state";
+        let expected = "Projection: #id, #first_name, #last_name\
                         \n  Filter: NOT #state\
                         \n    TableScan: person projection=None";
         quick_test(sql, expected);
     }
 
     #[test]
-    fn select_compound_filter() {
-        let sql = "SELECT id, first_name, last_name \
-                   FROM person WHERE state = 'CO' AND age >= 21 AND age <= 65";
-        let expected = "Projection: #id, #first_name, #last_name\
-            \n  Filter: #state Eq Utf8(\"CO\") And #age GtEq Int64(21) And #age LtEq Int64(65)\
-            \n    TableScan: person projection=None";
-        quick_test(sql, expected);
+    fn select_compound_filter() {
+        let sql = "SELECT id, first_name, last_name \
+                   FROM person WHERE state = 'CO' AND age >= 21 AND age <= 65";
+        let expected = "Projection: #id, #first_name, #last_name\
+            \n  Filter: #state Eq Utf8(\"CO\") And #age GtEq Int64(21) And #age LtEq Int64(65)\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn test_timestamp_filter() {
+        let sql = "SELECT state FROM person WHERE birth_date < CAST (158412331400600000 as timestamp)";
+
+        let expected = "Projection: #state\
+            \n  Filter: #birth_date Lt CAST(Int64(158412331400600000) AS Timestamp(Nanosecond, None))\
+            \n    TableScan: person projection=None";
+
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_cast_boolean_integer() -> Result<()> {
+        let sql = "SELECT CAST(age > 21 AS INT), CAST(1 AS BOOLEAN) FROM person";
+        let expected =
+            "Projection: CAST(#age Gt Int64(21) AS Int32), CAST(Int64(1) AS Boolean)\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+        let plan = logical_plan(sql)?;
+        assert_eq!(&DataType::Int32, plan.schema().field(0).data_type());
+        assert_eq!(&DataType::Boolean, plan.schema().field(1).data_type());
+
+        let sql = "SELECT CAST(age > 21 AS DATE) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            format!(
+                "Plan(\"Cannot cast Boolean to Date32(Day) \
+                 (in expression 'CAST(age > 21 AS {})')\")",
+                SQLDataType::Date
+            ),
+            format!("{:?}", err)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn select_filter_is_null_expression() {
+        let sql = "SELECT id FROM person WHERE (age + salary) IS NULL";
+        let expected = "Projection: #id\
+                        \n  Filter: CAST(#age AS Float64) Plus #salary IS NULL\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT (age + salary) IS NOT NULL FROM person";
+        let expected = "Projection: CAST(#age AS Float64) Plus #salary IS NOT NULL\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_filter_is_null_function_and_cast() {
+        let sql = "SELECT id FROM person \
+                   WHERE sqrt(age) IS NULL OR CAST(state AS INT) IS NOT NULL";
+        let expected = "Projection: #id\
+                        \n  Filter: sqrt(#age) IS NULL Or CAST(#state AS Int32) IS NOT NULL\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_is_null_over_aggregate() {
+        let sql = "SELECT state, MAX(salary) + 1 IS NULL, MIN(age) IS NOT NULL \
+                   FROM person GROUP BY state";
+        let expected = "Projection: #state, #MAX(salary) Plus CAST(Int64(1) AS Float64) IS NULL, #MIN(age) IS NOT NULL\
+                        \n  Aggregate: groupBy=[[#state]], aggr=[[MAX(#salary), MIN(#age)]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_filter_null_comparison_rewritten() {
+        let planner =
+            SqlToRel::new(&MockSchemaProvider {}).with_rewrite_null_comparisons(true);
+
+        let sql = "SELECT id FROM person WHERE state = NULL";
+        let ast = DFParser::parse_sql(&sql).unwrap();
+        let plan = planner.statement_to_plan(&ast[0]).unwrap();
+        let expected = "Projection: #id\
+                        \n  Filter: #state IS NULL\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+        assert_eq!(1, planner.warnings().len());
+        assert!(planner.warnings()[0].contains("#state IS NULL"));
+
+        let sql = "SELECT id FROM person WHERE NULL != state";
+        let ast = DFParser::parse_sql(&sql).unwrap();
+        let plan = planner.statement_to_plan(&ast[0]).unwrap();
+        let expected = "Projection: #id\
+                        \n  Filter: #state IS NOT NULL\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+        assert_eq!(2, planner.warnings().len());
+    }
+
+    #[test]
+    fn select_filter_null_comparison_not_rewritten() {
+        let sql = "SELECT id FROM person WHERE state = NULL";
+        let expected = "Projection: #id\
+                        \n  Filter: #state Eq Utf8(\"NULL\")\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_filter_row_comparison() {
+        let sql = "SELECT id FROM person WHERE ROW(first_name, age) = ROW('Jo', 30)";
+        let expected = "Projection: #id\
+                        \n  Filter: #first_name Eq Utf8(\"Jo\") And #age Eq Int64(30)\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT id FROM person WHERE ROW(first_name, age) <> ROW('Jo', 30)";
+        let expected = "Projection: #id\
+                        \n  Filter: #first_name NotEq Utf8(\"Jo\") Or #age NotEq Int64(30)\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_filter_row_comparison_arity_mismatch() {
+        let err = logical_plan("SELECT id FROM person WHERE ROW(id, age) = ROW(1)")
+            .expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("Cannot compare rows of 2 and 1 elements"));
+    }
+
+    #[test]
+    fn select_all_boolean_operators() {
+        let sql = "SELECT age, first_name, last_name \
+                   FROM person \
+                   WHERE age = 21 \
+                   AND age != 21 \
+                   AND age > 21 \
+                   AND age >= 21 \
+                   AND age < 65 \
+                   AND age <= 65";
+        let expected = "Projection: #age, #first_name, #last_name\
+                        \n  Filter: #age Eq Int64(21) \
+                        And #age NotEq Int64(21) \
+                        And #age Gt Int64(21) \
+                        And #age GtEq Int64(21) \
+                        And #age Lt Int64(65) \
+                        And #age LtEq Int64(65)\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_user_variable() {
+        let sql = "SELECT @@version, id FROM person WHERE age > @min_age";
+        let expected = "Projection: @@version, #id\
+                        \n  Filter: #age Gt @min_age\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_unknown_variable() {
+        let sql = "SELECT @nope FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!("Plan(\"Unknown variable \'@nope\'\")", format!("{:?}", err));
+
+        let sql = "SELECT @nope + 1 FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Unknown variable \'@nope\' (in expression \'@nope + 1\')\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_binary_expr() {
+        let sql = "SELECT age + salary from person";
+        let expected = "Projection: CAST(#age AS Float64) Plus #salary\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_mixed_arithmetic() {
+        let sql = "SELECT c3 * c11, c12 - c4, c4 % c3, c5 / 0 FROM aggregate_test_100";
+        let expected = "Projection: CAST(#c3 AS Float32) Multiply #c11, \
+                        #c12 Minus CAST(#c4 AS Float64), #c4 Modulus CAST(#c3 AS Int16), \
+                        CAST(#c5 AS Int64) Divide Int64(0)\
+                        \n  TableScan: aggregate_test_100 projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_binary_expr_nested() {
+        let sql = "SELECT (age + salary)/2 from person";
+        let expected =
+            "Projection: CAST(#age AS Float64) Plus #salary Divide CAST(Int64(2) AS Float64)\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_simple_aggregate() {
+        quick_test(
+            "SELECT MIN(age) FROM person",
+            "Aggregate: groupBy=[[]], aggr=[[MIN(#age)]]\
+             \n  TableScan: person projection=None",
+        );
+    }
+
+    #[test]
+    fn test_sum_aggregate() {
+        quick_test(
+            "SELECT SUM(age) from person",
+            "Aggregate: groupBy=[[]], aggr=[[SUM(#age)]]\
+             \n  TableScan: person projection=None",
+        );
+    }
+
+    #[test]
+    fn select_simple_aggregate_with_groupby() {
+        quick_test(
+            "SELECT state, MIN(age), MAX(age) FROM person GROUP BY state",
+            "Aggregate: groupBy=[[#state]], aggr=[[MIN(#age), MAX(#age)]]\
+             \n  TableScan: person projection=None",
+        );
+    }
+
+    #[test]
+    fn test_wildcard() {
+        quick_test(
+            "SELECT * from person",
+            "Projection: #id, #first_name, #last_name, #age, #state, #salary, #birth_date\
+            \n  TableScan: person projection=None",
+        );
+    }
+
+    #[test]
+    fn select_window_function() {
+        let sql =
+            "SELECT id, ROW_NUMBER() OVER (PARTITION BY state ORDER BY age) FROM person";
+        let expected = "Projection: #id, #ROW_NUMBER() OVER (PARTITION BY state ORDER BY age ASC NULLS FIRST)\
+            \n  Window: windowExpr=[[ROW_NUMBER() OVER (PARTITION BY #state ORDER BY #age ASC NULLS FIRST)]]\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_multiple_window_functions() {
+        let sql = "SELECT state, RANK() OVER (ORDER BY salary DESC) AS salary_rank, \
+                   SUM(salary) OVER (PARTITION BY state) FROM person";
+        let expected = "Projection: #state, #RANK() OVER (ORDER BY salary DESC NULLS FIRST) AS salary_rank, #SUM(salary) OVER (PARTITION BY state)\
+            \n  Window: windowExpr=[[RANK() OVER (ORDER BY #salary DESC NULLS FIRST), SUM(#salary) OVER (PARTITION BY #state)]]\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_first_and_last_value() {
+        let sql = "SELECT id, FIRST_VALUE(age) OVER (PARTITION BY state ORDER BY id), \
+                   LAST_VALUE(age) OVER (PARTITION BY state ORDER BY id) FROM person";
+        let expected = "Projection: #id, \
+            #FIRST_VALUE(age) OVER (PARTITION BY state ORDER BY id ASC NULLS FIRST), \
+            #LAST_VALUE(age) OVER (PARTITION BY state ORDER BY id ASC NULLS FIRST)\
+            \n  Window: windowExpr=[[\
+            FIRST_VALUE(#age) OVER (PARTITION BY #state ORDER BY #id ASC NULLS FIRST), \
+            LAST_VALUE(#age) OVER (PARTITION BY #state ORDER BY #id ASC NULLS FIRST)]]\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT FIRST_VALUE(age) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The window function FIRST_VALUE requires an OVER clause \
+             (in expression 'FIRST_VALUE(age)')\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_lag() {
+        let sql = "SELECT id, LAG(age) OVER (ORDER BY id) FROM person";
+        let expected = "Projection: #id, #LAG(age) OVER (ORDER BY id ASC NULLS FIRST)\
+            \n  Window: windowExpr=[[LAG(#age) OVER (ORDER BY #id ASC NULLS FIRST)]]\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT id, LAG(age, 1, 0) OVER (ORDER BY id) FROM person";
+        let expected = "Projection: #id, #LAG(age,Int64(1),Int64(0)) OVER (ORDER BY id ASC NULLS FIRST)\
+            \n  Window: windowExpr=[[LAG(#age, Int64(1), Int64(0)) OVER (ORDER BY #id ASC NULLS FIRST)]]\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT LEAD(age) OVER (PARTITION BY state) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The window function LEAD requires an ORDER BY in its window \
+             (in expression 'LEAD(age) OVER (PARTITION BY state)')\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "SELECT LAG(age, id) OVER (ORDER BY id) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The offset of LAG must be a non-negative integer literal, found #id \
+             (in expression 'LAG(age, id) OVER (ORDER BY id)')\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_unknown_window_function() {
+        let sql = "SELECT sqrt(age) OVER () FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"There is no built-in window function named sqrt \
+             (in expression 'sqrt(age) OVER ()')\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_from_derived_table_alias() {
+        let sql = "SELECT renamed FROM (SELECT id AS renamed FROM person) t";
+        let expected = "Projection: #renamed\
+                        \n  Projection: #id AS renamed\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // the base column name is not visible outside of the derived table
+        let sql = "SELECT id FROM (SELECT id AS renamed FROM person) t";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("Invalid identifier 'id'"));
+    }
+
+    #[test]
+    fn select_qualified_column_of_derived_table() {
+        let sql = "SELECT t.a FROM (SELECT age AS a FROM person) t WHERE t.a > 21";
+        let expected = "Projection: #a\
+                        \n  Filter: #a Gt Int64(21)\
+                        \n    Projection: #age AS a\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // the columns belong to the alias of the derived table only
+        let sql = "SELECT person.a FROM (SELECT age AS a FROM person) t";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("Invalid compound identifier"));
+    }
+
+    #[test]
+    fn select_where_alias_reference() {
+        let sql = "SELECT age * 2 AS d FROM person WHERE d > 10";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).starts_with("Plan(\"Invalid identifier 'd'"));
+
+        let planner =
+            SqlToRel::new(&MockSchemaProvider {}).with_where_alias_references(true);
+        let plan = planner.sql_to_plan(sql).unwrap();
+        let expected = "Projection: CAST(#age AS Int64) Multiply Int64(2) AS d\
+                        \n  Filter: CAST(#age AS Int64) Multiply Int64(2) Gt Int64(10)\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        // a column of the input takes precedence over an alias
+        let sql = "SELECT age * 2 AS age FROM person WHERE age > 10";
+        let plan = planner.sql_to_plan(sql).unwrap();
+        let expected = "Projection: CAST(#age AS Int64) Multiply Int64(2) AS age\
+                        \n  Filter: #age Gt Int64(10)\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let sql = "SELECT COUNT(*) AS c FROM person WHERE c > 1";
+        let err = planner
+            .sql_to_plan(sql)
+            .expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"aggregate functions are not allowed in WHERE\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_with_settings() {
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        let sql = "SELECT id FROM person LIMIT 10 SETTINGS max_rows = 5, cache = 1";
+        let ast = DFParser::parse_sql(&sql).unwrap();
+        let plan = planner.statement_to_plan(&ast[0]).unwrap();
+        let expected = "Settings: max_rows=5\
+                        \n  Limit: 10\
+                        \n    Projection: #id\
+                        \n      TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+        assert_eq!(
+            vec!["Ignoring unknown setting 'cache'".to_string()],
+            planner.warnings()
+        );
+    }
+
+    #[test]
+    fn select_count_one() {
+        let sql = "SELECT COUNT(1) FROM person";
+        let expected = "Aggregate: groupBy=[[]], aggr=[[COUNT(UInt8(1))]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_count_star_name() -> Result<()> {
+        let plan = logical_plan("SELECT COUNT(*) FROM person")?;
+        assert_eq!("COUNT(*)", plan.schema().field(0).name());
+
+        let plan = logical_plan("SELECT COUNT(DISTINCT 1) FROM person")?;
+        assert_eq!("COUNT(DISTINCT *)", plan.schema().field(0).name());
+        Ok(())
+    }
+
+    #[test]
+    fn select_count_and_count_distinct() -> Result<()> {
+        let sql = "SELECT COUNT(state), COUNT(DISTINCT state) FROM person";
+        let expected =
+            "Aggregate: groupBy=[[]], aggr=[[COUNT(#state), COUNT(DISTINCT #state)]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let plan = logical_plan(sql)?;
+        let names: Vec<&String> =
+            plan.schema().fields().iter().map(|f| f.name()).collect();
+        assert_eq!(vec!["COUNT(state)", "COUNT(DISTINCT state)"], names);
+        Ok(())
+    }
+
+    #[test]
+    fn select_sum_and_avg_distinct() -> Result<()> {
+        let sql = "SELECT SUM(age), SUM(DISTINCT age), AVG(DISTINCT salary) FROM person";
+        let expected = "Aggregate: groupBy=[[]], \
+                        aggr=[[SUM(#age), SUM(DISTINCT #age), AVG(DISTINCT #salary)]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let plan = logical_plan(sql)?;
+        let names: Vec<&String> =
+            plan.schema().fields().iter().map(|f| f.name()).collect();
+        assert_eq!(
+            vec!["SUM(age)", "SUM(DISTINCT age)", "AVG(DISTINCT salary)"],
+            names
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn select_statistical_aggregates() {
+        let cases = vec![
+            ("STDDEV", "STDDEV"),
+            ("stddev_pop", "STDDEV_POP"),
+            ("VAR_SAMP", "VARIANCE"),
+            ("var_pop", "VAR_POP"),
+        ];
+        for (function, name) in cases {
+            let sql = format!(
+                "SELECT state, {}(salary) FROM person GROUP BY state",
+                function
+            );
+            let expected = format!(
+                "Aggregate: groupBy=[[#state]], aggr=[[{}(#salary)]]\
+                 \n  TableScan: person projection=None",
+                name
+            );
+            quick_test(&sql, &expected);
+        }
+    }
+
+    #[test]
+    fn select_array_agg() -> Result<()> {
+        let sql = "SELECT state, ARRAY_AGG(first_name) FROM person GROUP BY state";
+        let expected = "Aggregate: groupBy=[[#state]], aggr=[[ARRAY_AGG(#first_name)]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let plan = logical_plan(sql)?;
+        let field = plan.schema().field_with_name("ARRAY_AGG(first_name)")?;
+        assert_eq!(&DataType::List(Box::new(DataType::Utf8)), field.data_type());
+        Ok(())
+    }
+
+    #[test]
+    fn select_median_and_percentile() -> Result<()> {
+        let sql = "SELECT state, MEDIAN(salary), APPROX_PERCENTILE_CONT(salary, 0.95) \
+                   FROM person GROUP BY state";
+        let expected = "Aggregate: groupBy=[[#state]], \
+                        aggr=[[MEDIAN(#salary), APPROX_PERCENTILE_CONT(#salary, Float64(0.95))]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let plan = logical_plan(sql)?;
+        let field = plan.schema().field_with_name("MEDIAN(salary)")?;
+        assert_eq!(&DataType::Float64, field.data_type());
+
+        let sql = "SELECT APPROX_PERCENTILE_CONT(salary, 1.5) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The percentile of APPROX_PERCENTILE_CONT must be a float literal \
+             between 0 and 1, found Float64(1.5) \
+             (in expression 'APPROX_PERCENTILE_CONT(salary, 1.5)')\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "SELECT APPROX_PERCENTILE_CONT(salary, age) FROM person";
+        logical_plan(sql).expect_err("query should have failed");
+        Ok(())
+    }
+
+    #[test]
+    fn select_bool_and_or() -> Result<()> {
+        let sql = "SELECT state, BOOL_OR(age > 21), BOOL_AND(age > 21) FROM person GROUP BY state";
+        let expected = "Aggregate: groupBy=[[#state]], \
+                        aggr=[[BOOL_OR(#age Gt Int64(21)), BOOL_AND(#age Gt Int64(21))]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let plan = logical_plan(sql)?;
+        let field = plan
+            .schema()
+            .field_with_name("BOOL_AND(age Gt Int64(21))")?;
+        assert_eq!(&DataType::Boolean, field.data_type());
+
+        let sql = "SELECT BOOL_AND(age) FROM person";
+        logical_plan(sql).expect_err("query should have failed");
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_statements() {
+        let cases = vec![
+            (
+                "INSERT INTO person (id) VALUES (1)",
+                "INSERT is not supported",
+            ),
+            ("DELETE FROM person WHERE id = 1", "DELETE is not supported"),
+            ("DROP VIEW person", "DROP is not supported"),
+            ("COMMIT", "Transaction control is not supported"),
+        ];
+        for (sql, message) in cases {
+            let err = logical_plan(sql).expect_err("query should have failed");
+            assert_eq!(
+                format!("NotImplemented(\"{}\")", message),
+                format!("{:?}", err)
+            );
+        }
+    }
+
+    #[test]
+    fn referenced_tables() -> Result<()> {
+        let sql = "WITH young AS (SELECT id FROM person WHERE age < 30) \
+                   SELECT p.id FROM person p JOIN orders o ON p.id = o.customer_id \
+                   WHERE p.id IN (SELECT id FROM young) \
+                   AND EXISTS (SELECT * FROM lineitem WHERE price > 10)";
+        let statement = match &DFParser::parse_sql(sql)?[0] {
+            DFStatement::Statement(Statement::Query(query)) => query.clone(),
+            _ => unreachable!(),
+        };
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        assert_eq!(
+            vec!["person", "orders", "lineitem"],
+            planner.referenced_tables(&statement)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sql_to_plan() -> Result<()> {
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        let plan = planner.sql_to_plan("SELECT id FROM person WHERE age > 21")?;
+        assert_eq!(
+            "Projection: #id\
+             \n  Filter: #age Gt Int64(21)\
+             \n    TableScan: person projection=None",
+            format!("{:?}", plan)
+        );
+
+        let err = planner.sql_to_plan(" ;").unwrap_err();
+        assert_eq!(
+            "Plan(\"No SQL statement to plan in an empty input\")",
+            format!("{:?}", err)
+        );
+
+        let err = planner.sql_to_plan("SELEC id FROM person").unwrap_err();
+        assert!(matches!(err, DataFusionError::Plan(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn select_from_unknown_table() {
+        let err = logical_plan("SELECT id FROM persons").unwrap_err();
+        assert_eq!(
+            "Plan(\"no schema found for table persons; did you mean 'person'?; \
+             available tables are: aggregate_test_100, orders, person, states\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn sql_to_plan_with_dialect() -> Result<()> {
+        use sqlparser::dialect::{MySqlDialect, PostgreSqlDialect};
+
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        let expected = "Projection: #first_name\
+                        \n  TableScan: person projection=None";
+
+        let sql = "SELECT `first_name` FROM person";
+        let plan = planner.sql_to_plan_with_dialect(sql, &MySqlDialect {})?;
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let sql = "SELECT \"first_name\" FROM person";
+        let plan = planner.sql_to_plan_with_dialect(sql, &PostgreSqlDialect {})?;
+        assert_eq!(expected, format!("{:?}", plan));
+
+        // backticks do not quote identifiers in PostgreSQL
+        let sql = "SELECT `first_name` FROM person";
+        assert!(planner
+            .sql_to_plan_with_dialect(sql, &PostgreSqlDialect {})
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_statements() -> Result<()> {
+        let sql = "SELECT id FROM person; SELECT state FROM person WHERE age > 21";
+        let statements = DFParser::parse_sql(sql)?;
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        let plans = planner.statements_to_plans(&statements)?;
+        assert_eq!(2, plans.len());
+        assert_eq!(
+            "Projection: #id\n  TableScan: person projection=None",
+            format!("{:?}", plans[0])
+        );
+        assert_eq!(
+            "Projection: #state\
+             \n  Filter: #age Gt Int64(21)\
+             \n    TableScan: person projection=None",
+            format!("{:?}", plans[1])
+        );
+
+        let sql = "SELECT id FROM person; SELECT id FROM unknown";
+        let statements = DFParser::parse_sql(sql)?;
+        let err = planner.statements_to_plans(&statements).unwrap_err();
+        assert_eq!(
+            "Plan(\"Statement 1: Error during planning: no schema found for table unknown; \
+             available tables are: aggregate_test_100, orders, person, states\")",
+            format!("{:?}", err)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn select_invalid_identifier_suggestion() {
+        let sql = "SELECT frist_name FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).ends_with("; did you mean 'first_name'?\")"));
+
+        // no suggestion when nothing is close
+        let sql = "SELECT xyz FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(!format!("{:?}", err).contains("did you mean"));
+    }
+
+    #[test]
+    fn select_natural_join() {
+        let sql = "SELECT first_name, capital FROM person NATURAL JOIN states";
+        let expected = "Projection: #first_name, #capital\
+                        \n  Join: type=Inner, on=[#state = #state]\
+                        \n    TableScan: person projection=None\
+                        \n    TableScan: states projection=None";
+        quick_test(sql, expected);
+
+        // the common column appears once in the output
+        let sql = "SELECT * FROM person NATURAL LEFT JOIN states";
+        let expected =
+            "Projection: #id, #first_name, #last_name, #age, #state, #salary, \
+                        #birth_date, #capital\
+                        \n  Join: type=Left, on=[#state = #state]\
+                        \n    TableScan: person projection=None\
+                        \n    TableScan: states projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT * FROM person NATURAL JOIN orders";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"NATURAL JOIN requires the inputs to have columns in common\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_natural_outer_join_shared_column() -> Result<()> {
+        // the shared column is taken from the preserved input of a right join,
+        // and from either input of a full join, so it is never null
+        let cases = vec![
+            ("RIGHT", vec![true, false, false]),
+            ("FULL", vec![true, false, true]),
+        ];
+        for (join, expected) in cases {
+            let sql = format!(
+                "SELECT first_name, state, capital FROM person NATURAL {} JOIN states",
+                join
+            );
+            let plan = logical_plan(&sql)?;
+            let nullable: Vec<bool> = plan
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| f.is_nullable())
+                .collect();
+            assert_eq!(expected, nullable, "{}", sql);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn select_join_using() {
+        let sql = "SELECT first_name, capital FROM person JOIN states USING (state)";
+        let expected = "Projection: #first_name, #capital\
+                        \n  Join: type=Inner, on=[#state = #state]\
+                        \n    TableScan: person projection=None\
+                        \n    TableScan: states projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_join_on() {
+        let sql =
+            "SELECT order_id, first_name FROM person JOIN orders ON id = customer_id";
+        let expected = "Projection: #order_id, #first_name\
+                        \n  Join: type=Inner, on=[#id = #customer_id]\
+                        \n    TableScan: person projection=None\
+                        \n    TableScan: orders projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_join_on_two_keys() {
+        let sql = "SELECT order_id FROM person JOIN orders \
+                   ON customer_id = id AND (o_item_id = age)";
+        let expected = "Projection: #order_id\
+                        \n  Join: type=Inner, on=[#id = #customer_id, #age = #o_item_id]\
+                        \n    TableScan: person projection=None\
+                        \n    TableScan: orders projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_join_on_residual_predicate() {
+        let sql = "SELECT order_id FROM person JOIN orders \
+                   ON id = customer_id AND qty > 0 AND age < 30";
+        let expected = "Projection: #order_id\
+                        \n  Filter: #qty Gt Int64(0) And #age Lt Int64(30)\
+                        \n    Join: type=Inner, on=[#id = #customer_id]\
+                        \n      TableScan: person projection=None\
+                        \n      TableScan: orders projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT order_id FROM person LEFT JOIN orders ON id = customer_id AND qty > 0";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "NotImplemented(\"Only equalities between columns are supported in the ON clause of a Left join\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_from_alias_with_columns() {
+        let sql = "SELECT x, y FROM (SELECT 1, 2) AS t(x, y)";
+        let expected = "Projection: #x, #y\
+                        \n  Projection: #Int64(1) AS x, #Int64(2) AS y\
+                        \n    Projection: Int64(1), Int64(2)\
+                        \n      EmptyRelation";
+        quick_test(sql, expected);
+
+        let sql = "SELECT x FROM (SELECT 1, 2) AS t(x)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The alias t names 1 columns but the relation has 2 columns\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_with_relation_qualifier() {
+        let sql = "SELECT p.id FROM person p WHERE p.age > 21";
+        let expected = "Projection: #id\
+                        \n  Filter: #age Gt Int64(21)\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT person.id FROM person WHERE P.age > 21";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("Invalid compound identifier"));
+
+        let sql = "SELECT person.id FROM person WHERE PERSON.age > 21";
+        let expected = "Projection: #id\
+                        \n  Filter: #age Gt Int64(21)\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_invalid_identifier_names_expression() {
+        let sql = "SELECT age + 1, agee * 2 FROM person";
+        let err = format!("{:?}", logical_plan(sql).expect_err("query should fail"));
+        assert!(err.starts_with("Plan(\"Invalid identifier 'agee'"));
+        assert!(err.ends_with("(in expression 'agee * 2')\")"));
+
+        let sql = "SELECT id FROM person WHERE age > 1 AND foo(state) = 'CO'";
+        let err = format!("{:?}", logical_plan(sql).expect_err("query should fail"));
+        assert!(err.starts_with("Plan(\"Invalid function 'foo'"));
+        assert!(err.contains("(in expression 'age > 1 AND foo(state) = 'CO'')"));
+
+        // the innermost expression is named for an error within a subquery
+        let sql = "SELECT id FROM person WHERE id IN (SELECT agee + 1 FROM person)";
+        let err = format!("{:?}", logical_plan(sql).expect_err("query should fail"));
+        assert!(err.ends_with("(in expression 'agee + 1')\")"));
+    }
+
+    #[test]
+    fn referenced_columns_of_join_and_filter() -> Result<()> {
+        let columns = |pairs: Vec<(&str, &str)>| -> HashSet<(String, String)> {
+            pairs
+                .into_iter()
+                .map(|(t, c)| (t.to_string(), c.to_string()))
+                .collect()
+        };
+
+        let sql = "SELECT order_id, first_name FROM person JOIN orders \
+                   ON id = customer_id WHERE qty > 1";
+        let plan = logical_plan(sql)?;
+        assert_eq!(
+            columns(vec![
+                ("person", "first_name"),
+                ("person", "id"),
+                ("orders", "order_id"),
+                ("orders", "customer_id"),
+                ("orders", "qty"),
+            ]),
+            utils::referenced_columns(&plan)?
+        );
+
+        // the columns read by a correlated subquery, in both queries
+        let sql = "SELECT first_name FROM person \
+                   WHERE EXISTS (SELECT 1 FROM orders WHERE customer_id = id)";
+        let plan = logical_plan(sql)?;
+        assert_eq!(
+            columns(vec![
+                ("person", "first_name"),
+                ("person", "id"),
+                ("orders", "customer_id"),
+            ]),
+            utils::referenced_columns(&plan)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn select_self_join() {
+        let sql = "SELECT a.id, b.first_name, last_name FROM person a \
+                   JOIN (SELECT id, first_name FROM person) b ON a.id = b.id";
+        let expected = "Projection: #a.id, #b.first_name, #a.last_name\
+                        \n  Join: type=Inner, on=[#a.id = #b.id]\
+                        \n    Projection: #id AS a.id, #first_name AS a.first_name, \
+                        #last_name AS a.last_name, #age AS a.age, #state AS a.state, \
+                        #salary AS a.salary, #birth_date AS a.birth_date\
+                        \n      TableScan: person projection=None\
+                        \n    Projection: #id AS b.id, #first_name AS b.first_name\
+                        \n      Projection: #id, #first_name\
+                        \n        TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // the column is in both inputs
+        let sql = "SELECT id FROM person a JOIN person b ON a.id = b.id";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).starts_with("Plan(\"Invalid identifier 'id'"));
+    }
+
+    #[test]
+    fn select_from_wide_schema() -> Result<()> {
+        let schema = Schema::new(
+            (0..200)
+                .map(|i| Field::new(&format!("c{}", i), DataType::Int32, false))
+                .collect(),
+        );
+        let ctx = PlannerContext::new(&schema);
+        assert_eq!("c150", ctx.field_with_name("c150").unwrap().name());
+        assert!(ctx.field_with_name("c200").is_none());
+
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        let predicate = (0..200)
+            .map(|i| format!("c{} > {}", i, i))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let sql = format!("SELECT * FROM t WHERE {}", predicate);
+        let selection = match &DFParser::parse_sql(&sql)?[0] {
+            DFStatement::Statement(Statement::Query(query)) => match &query.body {
+                SetExpr::Select(select) => select.selection.clone().unwrap(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        let expr = planner.sql_expr_to_rex(&selection, &ctx)?;
+        let mut columns = HashSet::new();
+        utils::expr_to_column_names(&expr, &mut columns)?;
+        assert_eq!(200, columns.len());
+        Ok(())
+    }
+
+    #[test]
+    fn quoted_and_unquoted_identifiers() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("First Name", DataType::Utf8, false),
+            Field::new("lastName", DataType::Utf8, false),
+        ]);
+        let ctx = PlannerContext::new(&schema);
+        let ident = |value: &str, quote_style: Option<char>| {
+            SQLExpr::Identifier(Ident {
+                value: value.to_string(),
+                quote_style,
+            })
+        };
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+
+        let expr = planner.sql_expr_to_rex(&ident("First Name", Some('"')), &ctx)?;
+        assert_eq!("#First Name", format!("{:?}", expr));
+
+        // unquoted identifiers ignore the case
+        let expr = planner.sql_expr_to_rex(&ident("LASTNAME", None), &ctx)?;
+        assert_eq!("#lastName", format!("{:?}", expr));
+
+        // quoted identifiers are case-sensitive
+        assert!(planner
+            .sql_expr_to_rex(&ident("lastname", Some('"')), &ctx)
+            .is_err());
+
+        let planner = planner.with_case_insensitive_identifiers(false);
+        assert!(planner
+            .sql_expr_to_rex(&ident("LASTNAME", None), &ctx)
+            .is_err());
+        let expr = planner.sql_expr_to_rex(&ident("lastName", None), &ctx)?;
+        assert_eq!("#lastName", format!("{:?}", expr));
+        Ok(())
+    }
+
+    #[test]
+    fn select_count_column() {
+        let sql = "SELECT COUNT(id) FROM person";
+        let expected = "Aggregate: groupBy=[[]], aggr=[[COUNT(#id)]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_scalar_func() {
+        let sql = "SELECT sqrt(age) FROM person";
+        let expected = "Projection: sqrt(#age)\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_string_literal_escapes() -> Result<()> {
+        // a quote inside a string literal is escaped by doubling it
+        let plan = logical_plan("SELECT 'it''s', ''''")?;
+        let values: Vec<String> = match plan {
+            LogicalPlan::Projection { expr, .. } => expr
+                .iter()
+                .map(|e| match e {
+                    Expr::Literal(ScalarValue::Utf8(Some(s))) => s.clone(),
+                    e => panic!("unexpected expression {:?}", e),
+                })
+                .collect(),
+            plan => panic!("unexpected plan {:?}", plan),
+        };
+        assert_eq!(vec!["it's", "'"], values);
+        Ok(())
+    }
+
+    #[test]
+    fn select_hex_and_national_string_literals() {
+        quick_test(
+            "SELECT X'1F', x'00ff' FROM person",
+            "Projection: Binary(\"1f\"), Binary(\"00ff\")\
+             \n  TableScan: person projection=None",
+        );
+        quick_test(
+            "SELECT N'abc' FROM person",
+            "Projection: Utf8(\"abc\")\
+             \n  TableScan: person projection=None",
+        );
+
+        for hex in &["1", "zz"] {
+            let sql = format!("SELECT X'{}' FROM person", hex);
+            let err = logical_plan(&sql).expect_err("query should have failed");
+            assert_eq!(
+                format!(
+                    "Plan(\"Invalid hex string literal X'{}' (in expression 'X'{}'')\")",
+                    hex, hex
+                ),
+                format!("{:?}", err)
+            );
+        }
+    }
+
+    #[test]
+    fn select_typed_string() {
+        let sql = "SELECT TIMESTAMP '2020-09-08T12:00:00Z', DATE '2020-09-08', \
+                   TIME '12:34:56.5' FROM person";
+        let expected = "Projection: CAST(Int64(1599566400000000000) AS Timestamp(Nanosecond, None)), \
+                        CAST(Int32(18513) AS Date32(Day)), \
+                        CAST(Int64(45296500000000) AS Time64(Nanosecond))\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_typed_string_invalid_value() {
+        let sql = "SELECT DATE '2020-13-01' FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Cannot parse '2020-13-01' as a value of type Date32(Day) \
+             (in expression 'DATE '2020-13-01'')\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_date_trunc_and_now() {
+        quick_test(
+            "SELECT date_trunc('month', birth_date), now() FROM person",
+            "Projection: date_trunc(Utf8(\"month\"), #birth_date), now()\
+             \n  TableScan: person projection=None",
+        );
+
+        let plan = logical_plan("SELECT now() FROM person").unwrap();
+        assert_eq!(
+            &DataType::Timestamp(TimeUnit::Nanosecond, None),
+            plan.schema().field(0).data_type()
+        );
+
+        let sql = "SELECT date_trunc('month', state) FROM person";
+        assert!(logical_plan(sql).is_err());
+    }
+
+    #[test]
+    fn select_mod_and_caret() {
+        quick_test(
+            "SELECT MOD(age, 2) FROM person",
+            "Projection: CAST(#age AS Int64) Modulus Int64(2)\
+             \n  TableScan: person projection=None",
+        );
+
+        // the caret is the power operator
+        quick_test(
+            "SELECT 2 ^ 3",
+            "Projection: power(Int64(2), Int64(3))\
+             \n  EmptyRelation",
+        );
+
+        let sql = "SELECT MOD(age) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The function MOD expects 2 arguments, found 1 \
+             (in expression 'MOD(age)')\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_regexp_functions() {
+        quick_test(
+            "SELECT regexp_match(first_name, '^J(.*)'), \
+             regexp_replace(last_name, 'a', 'b', 'g') FROM person",
+            "Projection: regexp_match(#first_name, Utf8(\"^J(.*)\")), \
+             regexp_replace(#last_name, Utf8(\"a\"), Utf8(\"b\"), Utf8(\"g\"))\
+             \n  TableScan: person projection=None",
+        );
+
+        // the pattern is only compiled at execution
+        quick_test(
+            "SELECT regexp_replace(last_name, '(', 'b') FROM person",
+            "Projection: regexp_replace(#last_name, Utf8(\"(\"), Utf8(\"b\"))\
+             \n  TableScan: person projection=None",
+        );
+
+        let plan = logical_plan("SELECT regexp_match(state, 'C') FROM person").unwrap();
+        assert_eq!(
+            &DataType::List(Box::new(DataType::Utf8)),
+            plan.schema().field(0).data_type()
+        );
+    }
+
+    #[test]
+    fn select_strpos_and_overlay() {
+        quick_test(
+            "SELECT strpos(first_name, 'a'), overlay(last_name, 'x', age, 1) FROM person",
+            "Projection: strpos(#first_name, Utf8(\"a\")), overlay(#last_name, Utf8(\"x\"), #age, Int64(1))\
+             \n  TableScan: person projection=None",
+        );
+        quick_test(
+            "SELECT overlay(last_name, 'x', 2) FROM person",
+            "Projection: overlay(#last_name, Utf8(\"x\"), Int64(2))\
+             \n  TableScan: person projection=None",
+        );
+
+        // the position and the number of characters must be integers
+        let sql = "SELECT overlay(last_name, 'x', salary) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("Coercion from [Utf8, Utf8, Float64]"));
+    }
+
+    #[test]
+    fn select_coalesce() {
+        let sql = "SELECT coalesce(first_name, last_name, state) FROM person";
+        let expected = "Projection: coalesce(#first_name, #last_name, #state)\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_trim_funcs() {
+        let sql = "SELECT trim(first_name), ltrim(first_name, 'x'), rtrim(last_name) FROM person";
+        let expected = "Projection: trim(#first_name), ltrim(#first_name, Utf8(\"x\")), rtrim(#last_name)\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_trim_too_many_args() {
+        let sql = "SELECT trim(first_name, 'x', 'y') FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Function 'trim' accepts at most two arguments, found 3\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_aliased_scalar_func() {
+        let sql = "SELECT sqrt(age) AS square_people FROM person";
+        let expected = "Projection: sqrt(#age) AS square_people\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_fetch_first() {
+        let sql = "SELECT id FROM person FETCH FIRST 5 ROWS ONLY";
+        let expected = "Limit: 5\
+                        \n  Projection: #id\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT id FROM person FETCH NEXT ROW ONLY";
+        let expected = "Limit: 1\
+                        \n  Projection: #id\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT id FROM person LIMIT 5 FETCH FIRST 5 ROWS ONLY";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"LIMIT and FETCH cannot be used in the same query\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "SELECT id FROM person ORDER BY id FETCH FIRST 5 ROWS WITH TIES";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "NotImplemented(\"FETCH ... WITH TIES is not supported\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_offset_fetch() {
+        let sql =
+            "SELECT id FROM person ORDER BY id OFFSET 5 ROWS FETCH NEXT 10 ROWS ONLY";
+        let expected = "Limit: 10\
+                        \n  Offset: 5\
+                        \n    Sort: #id ASC NULLS FIRST\
+                        \n      Projection: #id\
+                        \n        TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT id FROM person OFFSET 1 ROW FETCH FIRST 2 ROWS ONLY";
+        let expected = "Limit: 2\
+                        \n  Offset: 1\
+                        \n    Projection: #id\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT id FROM person LIMIT 3 OFFSET 2 ROWS";
+        let expected = "Limit: 3\
+                        \n  Offset: 2\
+                        \n    Projection: #id\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT id FROM person OFFSET -1 ROWS";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"OFFSET must not be negative, found -1\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_order_by() {
+        let sql = "SELECT id FROM person ORDER BY id";
+        let expected = "Sort: #id ASC NULLS FIRST\
+                        \n  Projection: #id\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_order_by_desc() {
+        let sql = "SELECT id FROM person ORDER BY id DESC";
+        let expected = "Sort: #id DESC NULLS FIRST\
+                        \n  Projection: #id\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_order_by_nulls_last() {
+        quick_test(
+            "SELECT id FROM person ORDER BY id DESC NULLS LAST",
+            "Sort: #id DESC NULLS LAST\
+            \n  Projection: #id\
+            \n    TableScan: person projection=None",
+        );
+
+        quick_test(
+            "SELECT id FROM person ORDER BY id NULLS LAST",
+            "Sort: #id ASC NULLS LAST\
+            \n  Projection: #id\
+            \n    TableScan: person projection=None",
+        );
+    }
+
+    #[test]
+    fn select_limit_expression() {
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        let input = LogicalPlanBuilder::empty().build().unwrap();
+        let number = |n: &str| Box::new(SQLExpr::Value(Value::Number(n.to_string())));
+
+        // LIMIT 10 + 5 * 2
+        let limit = SQLExpr::BinaryOp {
+            left: number("10"),
+            op: BinaryOperator::Plus,
+            right: Box::new(SQLExpr::BinaryOp {
+                left: number("5"),
+                op: BinaryOperator::Multiply,
+                right: number("2"),
+            }),
+        };
+        let plan = planner.limit(&input, &Some(limit)).unwrap();
+        assert_eq!("Limit: 20\n  EmptyRelation", format!("{:?}", plan));
+
+        // LIMIT 5 - 10
+        let limit = SQLExpr::BinaryOp {
+            left: number("5"),
+            op: BinaryOperator::Minus,
+            right: number("10"),
+        };
+        let err = planner.limit(&input, &Some(limit)).unwrap_err();
+        assert_eq!(
+            "Plan(\"LIMIT must not be negative, found -5\")",
+            format!("{:?}", err)
+        );
+
+        let err = logical_plan("SELECT id FROM person LIMIT 1.5")
+            .expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Unexpected expression for LIMIT clause: Float64(1.5), \
+             expected a constant integer expression\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_order_by_default_nulls_first() {
+        let sql = "SELECT id, age, state FROM person \
+                   ORDER BY id, age DESC NULLS FIRST, state NULLS LAST";
+        let ast = DFParser::parse_sql(&sql).unwrap();
+
+        let planner = SqlToRel::new(&MockSchemaProvider {});
+        let plan = planner.statement_to_plan(&ast[0]).unwrap();
+        let expected = "Sort: #id ASC NULLS FIRST, #age DESC NULLS FIRST, \
+                        #state ASC NULLS LAST\
+                        \n  Projection: #id, #age, #state\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let planner = planner.with_default_nulls_first(false);
+        let plan = planner.statement_to_plan(&ast[0]).unwrap();
+        let expected = "Sort: #id ASC NULLS LAST, #age DESC NULLS FIRST, \
+                        #state ASC NULLS LAST\
+                        \n  Projection: #id, #age, #state\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+    }
+
+    #[test]
+    fn select_order_by_mixed_directions() -> Result<()> {
+        let sql = "SELECT id, age, salary FROM person \
+                   ORDER BY age ASC NULLS LAST, salary DESC NULLS FIRST, id";
+        let expected = "Sort: #age ASC NULLS LAST, #salary DESC NULLS FIRST, \
+                        #id ASC NULLS FIRST\
+                        \n  Projection: #id, #age, #salary\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // each key keeps its own direction, in the order of the clause
+        let flags: Vec<(String, bool, bool)> = match logical_plan(sql)? {
+            LogicalPlan::Sort { expr, .. } => expr
+                .iter()
+                .map(|e| match e {
+                    Expr::Sort {
+                        expr,
+                        asc,
+                        nulls_first,
+                    } => (format!("{:?}", expr), *asc, *nulls_first),
+                    e => panic!("unexpected expression {:?}", e),
+                })
+                .collect(),
+            plan => panic!("unexpected plan {:?}", plan),
+        };
+        assert_eq!(
+            vec![
+                ("#age".to_string(), true, false),
+                ("#salary".to_string(), false, true),
+                ("#id".to_string(), true, true),
+            ],
+            flags
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn select_order_by_alias() {
+        let sql = "SELECT age AS a FROM person ORDER BY a";
+        let expected = "Sort: #a ASC NULLS FIRST\
+                        \n  Projection: #age AS a\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT state, MAX(age) AS oldest FROM person \
+                   GROUP BY state ORDER BY oldest DESC";
+        let expected = "Sort: #oldest DESC NULLS FIRST\
+                        \n  Aggregate: groupBy=[[#state]], aggr=[[MAX(#age) AS oldest]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_order_by_aggregate() {
+        let sql = "SELECT state, SUM(salary) FROM person \
+                   GROUP BY state ORDER BY SUM(salary) DESC";
+        let expected = "Sort: #SUM(salary) DESC NULLS FIRST\
+                        \n  Aggregate: groupBy=[[#state]], aggr=[[SUM(#salary)]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // the aggregate name is case insensitive
+        let sql = "SELECT SUM(salary), state FROM person \
+                   GROUP BY state ORDER BY sum(salary)";
+        let expected = "Sort: #SUM(salary) ASC NULLS FIRST\
+                        \n  Projection: #SUM(salary), #state\
+                        \n    Aggregate: groupBy=[[#state]], aggr=[[SUM(#salary)]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_order_by_unselected_aggregate() {
+        let sql = "SELECT state FROM person GROUP BY state ORDER BY COUNT(*)";
+        let expected = "Projection: #state\
+                        \n  Sort: #COUNT(*) ASC NULLS FIRST\
+                        \n    Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1))]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT MAX(age), state FROM person \
+                   GROUP BY state ORDER BY MIN(age) DESC, MAX(age)";
+        let expected = "Projection: #MAX(age), #state\
+                        \n  Sort: #MIN(age) DESC NULLS FIRST, #MAX(age) ASC NULLS FIRST\
+                        \n    Projection: #MAX(age), #state, #MIN(age)\
+                        \n      Aggregate: groupBy=[[#state]], aggr=[[MAX(#age), MIN(#age)]]\
+                        \n        TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_order_by_aggregate_with_having() {
+        let sql = "SELECT state FROM person GROUP BY state \
+                   HAVING COUNT(*) > 1 ORDER BY SUM(salary)";
+        let expected = "Projection: #state\
+                        \n  Sort: #SUM(salary) ASC NULLS FIRST\
+                        \n    Projection: #state, #SUM(salary)\
+                        \n      Filter: #COUNT(*) Gt Int64(1)\
+                        \n        Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1)), SUM(#salary)]]\
+                        \n          TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // the aggregate of the HAVING clause is already computed
+        let sql = "SELECT state FROM person GROUP BY state \
+                   HAVING COUNT(*) > 1 ORDER BY COUNT(*)";
+        let expected = "Projection: #state\
+                        \n  Sort: #COUNT(*) ASC NULLS FIRST\
+                        \n    Projection: #state, #COUNT(*)\
+                        \n      Filter: #COUNT(*) Gt Int64(1)\
+                        \n        Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1))]]\
+                        \n          TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_order_by_expression_over_aggregate() {
+        let sql =
+            "SELECT state, COUNT(*) FROM person GROUP BY state ORDER BY COUNT(*) + 1";
+        let expected = "Sort: CAST(#COUNT(*) AS Int64) Plus Int64(1) ASC NULLS FIRST\
+                        \n  Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1))]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT state FROM person GROUP BY state ORDER BY COUNT(*) + 1";
+        let expected = "Projection: #state\
+                        \n  Sort: CAST(#COUNT(*) AS Int64) Plus Int64(1) ASC NULLS FIRST\
+                        \n    Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1))]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT state FROM person GROUP BY state ORDER BY COUNT(*) + age";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"ORDER BY references the column 'age', which is neither grouped nor aggregated\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_group_by() {
+        let sql = "SELECT state FROM person GROUP BY state";
+        let expected = "Aggregate: groupBy=[[#state]], aggr=[[]]\
+                        \n  TableScan: person projection=None";
+
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_needs_projection() {
+        let sql = "SELECT COUNT(state), state FROM person GROUP BY state";
+        let expected = "\
+        Projection: #COUNT(state), #state\
+        \n  Aggregate: groupBy=[[#state]], aggr=[[COUNT(#state)]]\
+        \n    TableScan: person projection=None";
+
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_expression() {
+        let sql = "SELECT age + 1, COUNT(*) FROM person GROUP BY age + 1";
+        let expected =
+            "Aggregate: groupBy=[[CAST(#age AS Int64) Plus Int64(1)]], aggr=[[COUNT(UInt8(1))]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_expression_over_aggregate() {
+        let sql = "SELECT state, CAST(COUNT(*) AS FLOAT) FROM person GROUP BY state";
+        let expected = "Projection: #state, CAST(#COUNT(*) AS Float64)\
+                        \n  Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1))]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT MAX(age) - MIN(age) AS age_range, MIN(age) IS NULL FROM person";
+        let expected =
+            "Projection: #MAX(age) Minus #MIN(age) AS age_range, #MIN(age) IS NULL\
+                        \n  Aggregate: groupBy=[[]], aggr=[[MAX(#age), MIN(#age)]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT age + COUNT(*) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"SELECT references the column 'age', which is neither grouped nor aggregated\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_cast() {
+        let sql = "SELECT CAST(age AS VARCHAR), CAST('1' AS INT), CAST(NULL AS TIMESTAMP) FROM person";
+        let expected = "Projection: CAST(#age AS Utf8), CAST(Utf8(\"1\") AS Int32), \
+                        CAST(Utf8(\"NULL\") AS Timestamp(Nanosecond, None))\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_unsupported_cast() {
+        let sql = "SELECT CAST(birth_date AS BOOLEAN) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            format!(
+                "Plan(\"Cannot cast Timestamp(Nanosecond, None) to Boolean \
+                 (in expression 'CAST(birth_date AS {})')\")",
+                SQLDataType::Boolean
+            ),
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_group_by_cast_and_function() {
+        let sql = "SELECT CAST(age AS BIGINT), COUNT(*) FROM person GROUP BY CAST(AGE AS BIGINT)";
+        let expected =
+            "Aggregate: groupBy=[[CAST(#age AS Int64)]], aggr=[[COUNT(UInt8(1))]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT COUNT(*), sqrt(age) FROM person GROUP BY sqrt((age))";
+        let expected = "Projection: #COUNT(*), #sqrt(age)\
+                        \n  Aggregate: groupBy=[[sqrt(#age)]], aggr=[[COUNT(UInt8(1))]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_aliased_expression() {
+        let sql = "SELECT age + 1 AS next_age, COUNT(*) FROM person GROUP BY age + 1";
+        let expected = "Projection: #CAST(age AS Int64) Plus Int64(1) AS next_age, #COUNT(*)\
+                        \n  Aggregate: groupBy=[[CAST(#age AS Int64) Plus Int64(1)]], aggr=[[COUNT(UInt8(1))]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_projection_alias() {
+        let sql = "SELECT CAST(age AS BIGINT) AS big_age, COUNT(*) FROM person GROUP BY big_age";
+        let expected = "Projection: #CAST(age AS Int64) AS big_age, #COUNT(*)\
+                        \n  Aggregate: groupBy=[[CAST(#age AS Int64)]], aggr=[[COUNT(UInt8(1))]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // a column of the input takes precedence over an alias
+        let sql = "SELECT age + 1 AS age, COUNT(*) FROM person GROUP BY age";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Projection references non-aggregate values\")",
+            format!("{:?}", err)
+        );
+
+        // an aggregate cannot be grouped by
+        let sql = "SELECT COUNT(*) AS total FROM person GROUP BY total";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("Invalid identifier 'total'"));
+    }
+
+    #[test]
+    fn select_having_grouping_alias() {
+        let sql =
+            "SELECT state AS s, COUNT(*) FROM person GROUP BY state HAVING s = 'CO'";
+        let expected = "Projection: #state AS s, #COUNT(*)\
+                        \n  Filter: #state Eq Utf8(\"CO\")\
+                        \n    Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1))]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // aggregates that are not selected are computed and projected away
+        let sql = "SELECT state FROM person GROUP BY state HAVING COUNT(*) > 1";
+        let expected = "Projection: #state\
+                        \n  Filter: #COUNT(*) Gt Int64(1)\
+                        \n    Aggregate: groupBy=[[#state]], aggr=[[COUNT(UInt8(1))]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT state, COUNT(*) FROM person GROUP BY state HAVING age > 21";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"HAVING references the column 'age', which is neither grouped nor aggregated\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "SELECT state, COUNT(*) FROM person GROUP BY state HAVING st = 'CO'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).starts_with("Plan(\"Invalid identifier 'st'"));
+    }
+
+    #[test]
+    fn select_having_unprojected_aggregate() -> Result<()> {
+        let sql = "SELECT state FROM person GROUP BY state HAVING SUM(salary) > 1000";
+        let expected = "Projection: #state\
+                        \n  Filter: #SUM(salary) Gt Int64(1000)\
+                        \n    Aggregate: groupBy=[[#state]], aggr=[[SUM(#salary)]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+        let plan = logical_plan(sql)?;
+        let fields: Vec<&str> = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect();
+        assert_eq!(vec!["state"], fields);
+
+        // the selected aggregates are kept, and the added ones are projected away
+        let sql = "SELECT state, MAX(age) FROM person GROUP BY state \
+                   HAVING SUM(salary) > 1000 AND MAX(age) < 65";
+        let expected = "Projection: #state, #MAX(age)\
+                        \n  Filter: #SUM(salary) Gt Int64(1000) And #MAX(age) Lt Int64(65)\
+                        \n    Aggregate: groupBy=[[#state]], aggr=[[MAX(#age), SUM(#salary)]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn select_group_by_all() {
+        let sql = "SELECT state, age FROM person GROUP BY ALL";
+        let expected = "Aggregate: groupBy=[[#state, #age]], aggr=[[]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+        quick_test(
+            "SELECT state, age FROM person GROUP BY state, age",
+            expected,
+        );
+
+        let sql = "SELECT state, age + 1 AS next_age, COUNT(*) FROM person GROUP BY ALL";
+        let expected = "Projection: #state, #CAST(age AS Int64) Plus Int64(1) AS next_age, #COUNT(*)\
+                        \n  Aggregate: groupBy=[[#state, CAST(#age AS Int64) Plus Int64(1)]], aggr=[[COUNT(UInt8(1))]]\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_rollup() -> Result<()> {
+        let sql = "SELECT state, age, COUNT(*) FROM person GROUP BY ROLLUP (state, age)";
+        let expected = "Aggregate: groupBy=[[GROUPING SETS ((#state, #age), (#state), ())]], aggr=[[COUNT(UInt8(1))]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        // grouping columns are NULL in the rows of the sets they are not part of
+        let plan = logical_plan(sql)?;
+        assert!(plan.schema().field_with_name("state")?.is_nullable());
+        assert!(plan.schema().field_with_name("age")?.is_nullable());
+        Ok(())
+    }
+
+    #[test]
+    fn select_group_by_column_and_rollup() {
+        let sql = "SELECT state, age, COUNT(*) FROM person GROUP BY state, ROLLUP (age)";
+        let expected = "Aggregate: groupBy=[[GROUPING SETS ((#state, #age), (#state))]], aggr=[[COUNT(UInt8(1))]]\
+                        \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_rollup_not_projected() {
+        let sql = "SELECT first_name, COUNT(*) FROM person GROUP BY ROLLUP (state)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Projection references non-aggregate values\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_group_by_expression_not_projected() {
+        let sql = "SELECT age + 2, COUNT(*) FROM person GROUP BY age + 1";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Projection references non-aggregate values\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_7480_1() {
+        let sql = "SELECT c1, MIN(c12) FROM aggregate_test_100 GROUP BY c1, c13";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Projection references non-aggregate values\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_7480_2() {
+        let sql = "SELECT c1, c13, MIN(c12) FROM aggregate_test_100 GROUP BY c1";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Projection references non-aggregate values\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_exists_subquery() -> Result<()> {
+        let sql = "SELECT id FROM person \
+                   WHERE EXISTS (SELECT customer_id FROM orders WHERE qty > 1)";
+        let plan = logical_plan(sql)?;
+        let expected = "Projection: #id\
+                        \n  Filter: EXISTS (<subquery>)\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let expected = "Projection: #customer_id\
+                        \n  Filter: #qty Gt Int64(1)\
+                        \n    TableScan: orders projection=None";
+        assert_eq!(expected, format!("{:?}", filter_subquery(&plan)?));
+        Ok(())
+    }
+
+    #[test]
+    fn select_not_exists_correlated_subquery() -> Result<()> {
+        let sql = "SELECT id FROM person \
+                   WHERE NOT EXISTS (SELECT 1 FROM orders WHERE customer_id = id)";
+        let plan = logical_plan(sql)?;
+        let expected = "Projection: #id\
+                        \n  Filter: NOT EXISTS (<subquery>)\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        // `id` is resolved against the outer query
+        let expected = "Projection: Int64(1)\
+                        \n  Filter: #customer_id Eq outer_ref(#id)\
+                        \n    TableScan: orders projection=None";
+        assert_eq!(expected, format!("{:?}", filter_subquery(&plan)?));
+        Ok(())
+    }
+
+    #[test]
+    fn select_exists_correlated_qualified_reference() -> Result<()> {
+        let sql = "SELECT id FROM person \
+                   WHERE EXISTS (SELECT 1 FROM orders o WHERE o.customer_id = person.id)";
+        let plan = logical_plan(sql)?;
+        let expected = "Projection: Int64(1)\
+                        \n  Filter: #customer_id Eq outer_ref(#id)\
+                        \n    TableScan: orders projection=None";
+        assert_eq!(expected, format!("{:?}", filter_subquery(&plan)?));
+
+        // a reference to the outermost query from a nested subquery
+        let sql = "SELECT id FROM person p WHERE EXISTS (SELECT 1 FROM orders \
+                   WHERE EXISTS (SELECT 1 FROM orders WHERE p.age > qty))";
+        let plan = logical_plan(sql)?;
+        let expected = "Projection: Int64(1)\
+                        \n  Filter: outer_ref(#age) Gt #qty\
+                        \n    TableScan: orders projection=None";
+        assert_eq!(
+            expected,
+            format!("{:?}", filter_subquery(&filter_subquery(&plan)?)?)
+        );
+
+        let sql = "SELECT id FROM person \
+                   WHERE EXISTS (SELECT 1 FROM orders WHERE person.qty > 1)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("Invalid compound identifier"));
+        Ok(())
+    }
+
+    #[test]
+    fn select_aggregate_in_where() {
+        let sql = "SELECT * FROM person WHERE COUNT(*) > 1";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"aggregate functions are not allowed in WHERE\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_nested_aggregate() {
+        let sql = "SELECT MAX(COUNT(*)) FROM person GROUP BY state";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"aggregate function calls cannot be nested \
+             (in expression 'MAX(COUNT(*))')\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "SELECT SUM(age + MIN(age)) FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"aggregate function calls cannot be nested \
+             (in expression 'SUM(age + MIN(age))')\")",
+            format!("{:?}", err)
+        );
     }
 
     #[test]
-    fn test_timestamp_filter() {
-        let sql = "SELECT state FROM person WHERE birth_date < CAST (158412331400600000 as timestamp)";
-
-        let expected = "Projection: #state\
-            \n  Filter: #birth_date Lt CAST(Int64(158412331400600000) AS Timestamp(Nanosecond, None))\
-            \n    TableScan: person projection=None";
+    fn select_in_subquery() -> Result<()> {
+        let sql = "SELECT first_name FROM person \
+                   WHERE id IN (SELECT customer_id FROM orders WHERE qty > 1)";
+        let plan = logical_plan(sql)?;
+        let expected = "Projection: #first_name\
+                        \n  Filter: #id IN (<subquery>)\
+                        \n    TableScan: person projection=None";
+        assert_eq!(expected, format!("{:?}", plan));
 
-        quick_test(sql, expected);
+        let expected = "Projection: #customer_id\
+                        \n  Filter: #qty Gt Int64(1)\
+                        \n    TableScan: orders projection=None";
+        assert_eq!(expected, format!("{:?}", filter_subquery(&plan)?));
+        Ok(())
     }
 
     #[test]
-    fn select_all_boolean_operators() {
-        let sql = "SELECT age, first_name, last_name \
-                   FROM person \
-                   WHERE age = 21 \
-                   AND age != 21 \
-                   AND age > 21 \
-                   AND age >= 21 \
-                   AND age < 65 \
-                   AND age <= 65";
-        let expected = "Projection: #age, #first_name, #last_name\
-                        \n  Filter: #age Eq Int64(21) \
-                        And #age NotEq Int64(21) \
-                        And #age Gt Int64(21) \
-                        And #age GtEq Int64(21) \
-                        And #age Lt Int64(65) \
-                        And #age LtEq Int64(65)\
+    fn select_not_in_subquery() {
+        let sql = "SELECT first_name FROM person \
+                   WHERE id NOT IN (SELECT customer_id FROM orders)";
+        let expected = "Projection: #first_name\
+                        \n  Filter: #id NOT IN (<subquery>)\
                         \n    TableScan: person projection=None";
         quick_test(sql, expected);
     }
 
     #[test]
-    fn select_binary_expr() {
-        let sql = "SELECT age + salary from person";
-        let expected = "Projection: #age Plus #salary\
-                        \n  TableScan: person projection=None";
-        quick_test(sql, expected);
+    fn select_in_subquery_multiple_columns() {
+        let sql = "SELECT first_name FROM person \
+                   WHERE id IN (SELECT customer_id, qty FROM orders)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"IN subquery must return exactly one column, found 2 \
+             (in expression 'id IN (SELECT customer_id, qty FROM orders)')\")",
+            format!("{:?}", err)
+        );
     }
 
     #[test]
-    fn select_binary_expr_nested() {
-        let sql = "SELECT (age + salary)/2 from person";
-        let expected = "Projection: #age Plus #salary Divide Int64(2)\
-                        \n  TableScan: person projection=None";
+    fn create_external_table_csv() {
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV LOCATION 'foo.csv'";
+        let expected = "CreateExternalTable: \"t\"";
         quick_test(sql, expected);
     }
 
     #[test]
-    fn select_simple_aggregate() {
-        quick_test(
-            "SELECT MIN(age) FROM person",
-            "Aggregate: groupBy=[[]], aggr=[[MIN(#age)]]\
-             \n  TableScan: person projection=None",
+    fn create_external_table_csv_no_schema() {
+        let sql = "CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 'foo.csv'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Column definitions required for CSV files. None found\")",
+            format!("{:?}", err)
         );
     }
 
     #[test]
-    fn test_sum_aggregate() {
-        quick_test(
-            "SELECT SUM(age) from person",
-            "Aggregate: groupBy=[[]], aggr=[[SUM(#age)]]\
-             \n  TableScan: person projection=None",
+    fn create_external_table_csv_delimiter() -> Result<()> {
+        let cases = vec![
+            ("", b','),
+            ("WITH (DELIMITER '|')", b'|'),
+            ("WITH (DELIMITER '\\t')", b'\t'),
+        ];
+        for (options, expected) in cases {
+            let sql = format!(
+                "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV {} LOCATION 'foo.csv'",
+                options
+            );
+            match logical_plan(&sql)? {
+                LogicalPlan::CreateExternalTable { delimiter, .. } => {
+                    assert_eq!(expected, delimiter)
+                }
+                plan => panic!("unexpected plan {:?}", plan),
+            }
+        }
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV WITH (DELIMITER '||') \
+                   LOCATION 'foo.csv'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The delimiter must be a single character, found '||'\")",
+            format!("{:?}", err)
         );
+        Ok(())
     }
 
     #[test]
-    fn select_simple_aggregate_with_groupby() {
-        quick_test(
-            "SELECT state, MIN(age), MAX(age) FROM person GROUP BY state",
-            "Aggregate: groupBy=[[#state]], aggr=[[MIN(#age), MAX(#age)]]\
-             \n  TableScan: person projection=None",
+    fn create_external_table_compression() -> Result<()> {
+        let cases = vec![
+            ("", None),
+            ("WITH (COMPRESSION 'none')", None),
+            ("WITH (COMPRESSION 'GZIP')", Some("gzip".to_string())),
+            ("WITH (COMPRESSION 'zstd')", Some("zstd".to_string())),
+        ];
+        for (options, expected) in cases {
+            let sql = format!(
+                "CREATE EXTERNAL TABLE t STORED AS PARQUET {} LOCATION 'foo.parquet'",
+                options
+            );
+            match logical_plan(&sql)? {
+                LogicalPlan::CreateExternalTable { compression, .. } => {
+                    assert_eq!(expected, compression)
+                }
+                plan => panic!("unexpected plan {:?}", plan),
+            }
+        }
+
+        let sql = "CREATE EXTERNAL TABLE t STORED AS PARQUET WITH (COMPRESSION 'lz4') \
+                   LOCATION 'foo.parquet'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Unsupported compression codec 'lz4', expected one of none, \
+             gzip, zstd or snappy\")",
+            format!("{:?}", err)
         );
+        Ok(())
     }
 
     #[test]
-    fn test_wildcard() {
-        quick_test(
-            "SELECT * from person",
-            "Projection: #id, #first_name, #last_name, #age, #state, #salary, #birth_date\
-            \n  TableScan: person projection=None",
+    fn create_external_table_partitioned() -> Result<()> {
+        let sql = "CREATE EXTERNAL TABLE t(c1 int, year int) STORED AS CSV \
+                   PARTITIONED BY (year, month) LOCATION 'foo'";
+        match logical_plan(sql)? {
+            LogicalPlan::CreateExternalTable {
+                schema,
+                partition_columns,
+                ..
+            } => {
+                assert_eq!(vec!["year", "month"], partition_columns);
+                let fields = schema
+                    .fields()
+                    .iter()
+                    .map(|f| format!("{}: {:?}", f.name(), f.data_type()))
+                    .collect::<Vec<_>>();
+                assert_eq!(vec!["c1: Int32", "year: Int32", "month: Utf8"], fields);
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV \
+                   PARTITIONED BY (year, year) LOCATION 'foo'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Partition column year is specified more than once\")",
+            format!("{:?}", err)
         );
+        Ok(())
     }
 
     #[test]
-    fn select_count_one() {
-        let sql = "SELECT COUNT(1) FROM person";
-        let expected = "Aggregate: groupBy=[[]], aggr=[[COUNT(UInt8(1))]]\
-                        \n  TableScan: person projection=None";
-        quick_test(sql, expected);
+    fn create_external_table_char_length() -> Result<()> {
+        let sql = "CREATE EXTERNAL TABLE t(code CHAR(4), name VARCHAR(10), year int) \
+                   STORED AS CSV PARTITIONED BY (year, month) LOCATION 'foo'";
+        match logical_plan(sql)? {
+            LogicalPlan::CreateExternalTable { schema, .. } => {
+                assert_eq!(&DataType::Utf8, schema.field_with_name("code")?.data_type());
+                let mut expected = HashMap::new();
+                expected.insert("code.char_length".to_string(), "4".to_string());
+                assert_eq!(&expected, schema.metadata());
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+        Ok(())
     }
 
     #[test]
-    fn select_count_column() {
-        let sql = "SELECT COUNT(id) FROM person";
-        let expected = "Aggregate: groupBy=[[]], aggr=[[COUNT(#id)]]\
-                        \n  TableScan: person projection=None";
-        quick_test(sql, expected);
+    fn create_external_table_if_not_exists() -> Result<()> {
+        let cases = vec![("", false), ("IF NOT EXISTS", true)];
+        for (clause, expected) in cases {
+            let sql = format!(
+                "CREATE EXTERNAL TABLE {} t STORED AS PARQUET LOCATION 'foo.parquet'",
+                clause
+            );
+            match logical_plan(&sql)? {
+                LogicalPlan::CreateExternalTable { if_not_exists, .. } => {
+                    assert_eq!(expected, if_not_exists)
+                }
+                plan => panic!("unexpected plan {:?}", plan),
+            }
+        }
+        Ok(())
     }
 
     #[test]
-    fn select_scalar_func() {
-        let sql = "SELECT sqrt(age) FROM person";
-        let expected = "Projection: sqrt(#age)\
-                        \n  TableScan: person projection=None";
+    fn select_from_qualified_table() {
+        let sql = "SELECT id FROM public.person";
+        let expected = "Projection: #id\
+                        \n  TableScan: public.person projection=None";
         quick_test(sql, expected);
-    }
 
-    #[test]
-    fn select_aliased_scalar_func() {
-        let sql = "SELECT sqrt(age) AS square_people FROM person";
-        let expected = "Projection: sqrt(#age) AS square_people\
-                        \n  TableScan: person projection=None";
-        quick_test(sql, expected);
-    }
+        let plan = logical_plan(sql).unwrap();
+        match utils::inputs(&plan)[0] {
+            LogicalPlan::TableScan { schema_name, .. } => {
+                assert_eq!("public", schema_name)
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
 
-    #[test]
-    fn select_order_by() {
-        let sql = "SELECT id FROM person ORDER BY id";
-        let expected = "Sort: #id ASC NULLS FIRST\
-                        \n  Projection: #id\
-                        \n    TableScan: person projection=None";
-        quick_test(sql, expected);
+        // the schema is part of the name the table is looked up by
+        let err = logical_plan("SELECT id FROM other.person")
+            .expect_err("query should have failed");
+        assert!(format!("{:?}", err).contains("no schema found for table other.person"));
     }
 
     #[test]
-    fn select_order_by_desc() {
-        let sql = "SELECT id FROM person ORDER BY id DESC";
-        let expected = "Sort: #id DESC NULLS FIRST\
-                        \n  Projection: #id\
-                        \n    TableScan: person projection=None";
+    fn select_from_catalog_qualified_table() {
+        let sql = "SELECT id FROM main.public.person";
+        let expected = "Projection: #id\
+                        \n  TableScan: main.public.person projection=None";
         quick_test(sql, expected);
+
+        let plan = logical_plan(sql).unwrap();
+        match utils::inputs(&plan)[0] {
+            LogicalPlan::TableScan { schema_name, .. } => {
+                assert_eq!("public", schema_name)
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        // an unknown catalog is reported before the table is looked up
+        let err = logical_plan("SELECT id FROM other.public.person")
+            .expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"no catalog found named other\")",
+            format!("{:?}", err)
+        );
+
+        let err = logical_plan("SELECT id FROM main.public.orders")
+            .expect_err("query should have failed");
+        assert!(
+            format!("{:?}", err).contains("no schema found for table main.public.orders")
+        );
     }
 
     #[test]
-    fn select_order_by_nulls_last() {
+    fn drop_table() {
+        quick_test("DROP TABLE person", "DropTable: \"person\"");
         quick_test(
-            "SELECT id FROM person ORDER BY id DESC NULLS LAST",
-            "Sort: #id DESC NULLS LAST\
-            \n  Projection: #id\
-            \n    TableScan: person projection=None",
+            "DROP TABLE IF EXISTS nope",
+            "DropTable: \"nope\" if_exists=true",
+        );
+
+        let err = logical_plan("DROP TABLE nope").expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Table nope does not exist; \
+             available tables are: aggregate_test_100, orders, person, states\")",
+            format!("{:?}", err)
         );
+    }
 
+    #[test]
+    fn update() {
         quick_test(
-            "SELECT id FROM person ORDER BY id NULLS LAST",
-            "Sort: #id ASC NULLS LAST\
-            \n  Projection: #id\
-            \n    TableScan: person projection=None",
+            "UPDATE person SET age = age + 1 WHERE state = 'CO'",
+            "Update: \"person\" set=[age = CAST(CAST(#age AS Int64) Plus Int64(1) AS Int32)]\
+             \n  Filter: #state Eq Utf8(\"CO\")\
+             \n    TableScan: person projection=None",
         );
     }
 
     #[test]
-    fn select_group_by() {
-        let sql = "SELECT state FROM person GROUP BY state";
-        let expected = "Aggregate: groupBy=[[#state]], aggr=[[]]\
-                        \n  TableScan: person projection=None";
+    fn update_case_insensitive_column() {
+        quick_test(
+            "UPDATE person SET AGE = 1",
+            "Update: \"person\" set=[age = CAST(Int64(1) AS Int32)]\
+             \n  TableScan: person projection=None",
+        );
 
-        quick_test(sql, expected);
+        let err = logical_plan("UPDATE person SET \"AGE\" = 1")
+            .expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Unknown column 'AGE' in UPDATE of table person\")",
+            format!("{:?}", err)
+        );
     }
 
     #[test]
-    fn select_group_by_needs_projection() {
-        let sql = "SELECT COUNT(state), state FROM person GROUP BY state";
-        let expected = "\
-        Projection: #COUNT(state), #state\
-        \n  Aggregate: groupBy=[[#state]], aggr=[[COUNT(#state)]]\
-        \n    TableScan: person projection=None";
-
-        quick_test(sql, expected);
+    fn update_column_assigned_twice() {
+        for sql in &[
+            "UPDATE person SET age = 1, age = 2",
+            "UPDATE person SET age = 1, AGE = 2",
+        ] {
+            let err = logical_plan(sql).expect_err("query should have failed");
+            assert_eq!(
+                "Plan(\"Column 'age' is assigned more than once in UPDATE of table person\")",
+                format!("{:?}", err)
+            );
+        }
     }
 
     #[test]
-    fn select_7480_1() {
-        let sql = "SELECT c1, MIN(c12) FROM aggregate_test_100 GROUP BY c1, c13";
-        let err = logical_plan(sql).expect_err("query should have failed");
+    fn update_unknown_column() {
+        let err = logical_plan("UPDATE person SET height = 180 WHERE id = 1")
+            .expect_err("query should have failed");
         assert_eq!(
-            "Plan(\"Projection references non-aggregate values\")",
+            "Plan(\"Unknown column 'height' in UPDATE of table person\")",
             format!("{:?}", err)
         );
     }
 
     #[test]
-    fn select_7480_2() {
-        let sql = "SELECT c1, c13, MIN(c12) FROM aggregate_test_100 GROUP BY c1";
-        let err = logical_plan(sql).expect_err("query should have failed");
+    fn describe_table() {
+        quick_test("DESCRIBE person", "DescribeTable: \"person\"");
+        quick_test("SHOW COLUMNS FROM person", "DescribeTable: \"person\"");
+
+        match logical_plan("DESCRIBE person").unwrap() {
+            LogicalPlan::DescribeTable {
+                table_schema,
+                schema,
+                ..
+            } => {
+                let columns: Vec<(&str, &DataType)> = table_schema
+                    .fields()
+                    .iter()
+                    .map(|f| (f.name().as_str(), f.data_type()))
+                    .collect();
+                assert_eq!(
+                    vec![
+                        ("id", &DataType::UInt32),
+                        ("first_name", &DataType::Utf8),
+                        ("last_name", &DataType::Utf8),
+                        ("age", &DataType::Int32),
+                        ("state", &DataType::Utf8),
+                        ("salary", &DataType::Float64),
+                        (
+                            "birth_date",
+                            &DataType::Timestamp(TimeUnit::Nanosecond, None)
+                        ),
+                    ],
+                    columns
+                );
+                assert_eq!(LogicalPlan::describe_schema(), schema);
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        let err = logical_plan("DESCRIBE nope").expect_err("query should have failed");
         assert_eq!(
-            "Plan(\"Projection references non-aggregate values\")",
+            "Plan(\"Table nope does not exist; \
+             available tables are: aggregate_test_100, orders, person, states\")",
             format!("{:?}", err)
         );
     }
 
     #[test]
-    fn create_external_table_csv() {
-        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV LOCATION 'foo.csv'";
-        let expected = "CreateExternalTable: \"t\"";
-        quick_test(sql, expected);
-    }
-
-    #[test]
-    fn create_external_table_csv_no_schema() {
-        let sql = "CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 'foo.csv'";
-        let err = logical_plan(sql).expect_err("query should have failed");
-        assert_eq!(
-            "Plan(\"Column definitions required for CSV files. None found\")",
-            format!("{:?}", err)
+    fn explain_analyze() {
+        quick_test(
+            "EXPLAIN ANALYZE SELECT id FROM person",
+            "Analyze\
+             \n  Projection: #id\
+             \n    TableScan: person projection=None",
+        );
+        quick_test(
+            "EXPLAIN ANALYZE VERBOSE SELECT id FROM person",
+            "Analyze: verbose=true\
+             \n  Projection: #id\
+             \n    TableScan: person projection=None",
         );
     }
 
@@ -898,6 +5125,31 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn create_external_table_avro() {
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS AVRO LOCATION 'foo.avro'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Column definitions can not be specified for AVRO files.\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn create_external_table_avro_no_schema() {
+        let sql = "CREATE EXTERNAL TABLE t STORED AS AVRO LOCATION 'foo.avro'";
+        let expected = "CreateExternalTable: \"t\"";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn create_external_table_json() {
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS JSON LOCATION 'foo.json'";
+        quick_test(sql, "CreateExternalTable: \"t\"");
+        let sql = "CREATE EXTERNAL TABLE t STORED AS NDJSON LOCATION 'foo.json'";
+        quick_test(sql, "CreateExternalTable: \"t\"");
+    }
+
     fn logical_plan(sql: &str) -> Result<LogicalPlan> {
         let planner = SqlToRel::new(&MockSchemaProvider {});
         let ast = DFParser::parse_sql(&sql).unwrap();
@@ -910,24 +5162,49 @@ mod tests {
         assert_eq!(expected, format!("{:?}", plan));
     }
 
+    /// Returns the subquery of the first `Filter` with a subquery predicate
+    fn filter_subquery(plan: &LogicalPlan) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter {
+                predicate: Expr::Exists { subquery, .. },
+                ..
+            }
+            | LogicalPlan::Filter {
+                predicate: Expr::InSubquery { subquery, .. },
+                ..
+            } => Ok(subquery.as_ref().clone()),
+            LogicalPlan::Projection { input, .. }
+            | LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Offset { input, .. } => filter_subquery(input),
+            _ => Err(DataFusionError::Plan(format!(
+                "No subquery found in {:?}",
+                plan
+            ))),
+        }
+    }
+
     struct MockSchemaProvider {}
 
     impl SchemaProvider for MockSchemaProvider {
         fn get_table_meta(&self, name: &str) -> Option<SchemaRef> {
             match name {
-                "person" => Some(Arc::new(Schema::new(vec![
-                    Field::new("id", DataType::UInt32, false),
-                    Field::new("first_name", DataType::Utf8, false),
-                    Field::new("last_name", DataType::Utf8, false),
-                    Field::new("age", DataType::Int32, false),
-                    Field::new("state", DataType::Utf8, false),
-                    Field::new("salary", DataType::Float64, false),
-                    Field::new(
-                        "birth_date",
-                        DataType::Timestamp(TimeUnit::Nanosecond, None),
-                        false,
-                    ),
-                ]))),
+                "person" | "public.person" | "main.public.person" => {
+                    Some(Arc::new(Schema::new(vec![
+                        Field::new("id", DataType::UInt32, false),
+                        Field::new("first_name", DataType::Utf8, false),
+                        Field::new("last_name", DataType::Utf8, false),
+                        Field::new("age", DataType::Int32, false),
+                        Field::new("state", DataType::Utf8, false),
+                        Field::new("salary", DataType::Float64, false),
+                        Field::new(
+                            "birth_date",
+                            DataType::Timestamp(TimeUnit::Nanosecond, None),
+                            false,
+                        ),
+                    ])))
+                }
                 "aggregate_test_100" => Some(Arc::new(Schema::new(vec![
                     Field::new("c1", DataType::Utf8, false),
                     Field::new("c2", DataType::UInt32, false),
@@ -943,6 +5220,17 @@ mod tests {
                     Field::new("c12", DataType::Float64, false),
                     Field::new("c13", DataType::Utf8, false),
                 ]))),
+                "orders" => Some(Arc::new(Schema::new(vec![
+                    Field::new("order_id", DataType::UInt32, false),
+                    Field::new("customer_id", DataType::UInt32, false),
+                    Field::new("o_item_id", DataType::Utf8, false),
+                    Field::new("qty", DataType::Int32, false),
+                    Field::new("price", DataType::Float64, false),
+                ]))),
+                "states" => Some(Arc::new(Schema::new(vec![
+                    Field::new("state", DataType::Utf8, false),
+                    Field::new("capital", DataType::Utf8, false),
+                ]))),
                 _ => None,
             }
         }
@@ -962,7 +5250,26 @@ mod tests {
         }
 
         fn get_aggregate_meta(&self, _name: &str) -> Option<Arc<AggregateUDF>> {
-            unimplemented!()
+            None
+        }
+
+        fn has_catalog(&self, name: &str) -> bool {
+            name == "main"
+        }
+
+        fn table_names(&self) -> Vec<String> {
+            vec!["person", "aggregate_test_100", "orders", "states"]
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect()
+        }
+
+        fn get_variable_meta(&self, names: &[String]) -> Option<DataType> {
+            match names.join(".").as_str() {
+                "@@version" => Some(DataType::Utf8),
+                "@min_age" => Some(DataType::Int32),
+                _ => None,
+            }
         }
     }
 }
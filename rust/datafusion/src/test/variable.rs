@@ -20,6 +20,7 @@
 use crate::error::Result;
 use crate::scalar::ScalarValue;
 use crate::variable::VarProvider;
+use arrow::datatypes::DataType;
 
 /// System variable
 pub struct SystemVar {}
@@ -37,6 +38,10 @@ impl VarProvider for SystemVar {
         let s = format!("{}-{}", "system-var".to_string(), var_names.concat());
         Ok(ScalarValue::Utf8(Some(s)))
     }
+
+    fn get_type(&self, _: &[String]) -> Option<DataType> {
+        Some(DataType::Utf8)
+    }
 }
 
 /// user defined variable
@@ -55,4 +60,8 @@ impl VarProvider for UserDefinedVar {
         let s = format!("{}-{}", "user-defined-var".to_string(), var_names.concat());
         Ok(ScalarValue::Utf8(Some(s)))
     }
+
+    fn get_type(&self, _: &[String]) -> Option<DataType> {
+        Some(DataType::Utf8)
+    }
 }
@@ -19,6 +19,7 @@
 
 use crate::error::Result;
 use crate::scalar::ScalarValue;
+use arrow::datatypes::DataType;
 
 /// Variable type, system/user defined
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -33,4 +34,13 @@ pub enum VarType {
 pub trait VarProvider {
     /// Get variable value
     fn get_value(&self, var_names: Vec<String>) -> Result<ScalarValue>;
+
+    /// Get the data type of a variable, used to plan queries that reference it.
+    /// The default evaluates the variable via `get_value`; providers whose values
+    /// are expensive or have side effects should override it
+    fn get_type(&self, var_names: &[String]) -> Option<DataType> {
+        self.get_value(var_names.to_vec())
+            .ok()
+            .map(|value| value.get_datatype())
+    }
 }
@@ -1265,6 +1265,25 @@ async fn to_timstamp() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn query_typed_string_literals() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("ts_data", make_timestamp_nano_table()?);
+
+    let sql =
+        "SELECT COUNT(*) FROM ts_data where ts > TIMESTAMP '2020-09-08T12:00:00+00:00'";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![vec!["2"]];
+    assert_eq!(expected, actual);
+
+    let sql = "SELECT DATE '2020-09-08', TIME '12:34:56.5' FROM ts_data LIMIT 1";
+    let actual = execute(&mut ctx, sql).await;
+    // dates are printed as days and times as nanoseconds since midnight
+    let expected = vec![vec!["18513", "45296500000000"]];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn query_is_null() -> Result<()> {
     let schema = Arc::new(Schema::new(vec![Field::new("c1", DataType::Float64, true)]));